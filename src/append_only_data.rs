@@ -7,10 +7,19 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use crate::{utils, Error, PublicKey, Request, Result, XorName};
+use crate::{
+    deletion_payload, utils, DeletionProof, Error, Limits, OwnerSet, PublicKey, Request, Result,
+    Signature, Verifiable, VerificationError, XorName,
+};
+use bincode::serialized_size;
 use multibase::Decodable;
-use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter,
+    ops::Range,
+    result, u64,
+};
 
 pub type PubSeqAppendOnlyData = SeqAppendOnlyData<PubPermissions>;
 pub type PubUnseqAppendOnlyData = UnseqAppendOnlyData<PubPermissions>;
@@ -24,19 +33,66 @@ pub enum User {
     Key(PublicKey),
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub enum Action {
     Read,
     Append,
     ManagePermissions,
 }
 
+/// Reason why an action is denied to a key which doesn't satisfy the owners.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Denial {
+    /// The data has no permissions yet, so only its owners can act on it.
+    NoPermissions,
+    /// The current permissions have no entry for the key. `User::Anyone` isn't consulted then.
+    NoEntry,
+    /// The entry of the key doesn't allow the action.
+    NotAllowed,
+    /// The entry of the key leaves the action unset, and the entry of `User::Anyone` doesn't allow
+    /// it.
+    NotAllowedForAnyone,
+}
+
+/// Actions a key can perform on an AppendOnlyData, as enforced by `AData::check_permission`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EffectivePermissions {
+    /// Actions the key is allowed to perform.
+    pub allowed: BTreeSet<Action>,
+    /// Actions the key is denied, with the reason of each denial.
+    pub denied: BTreeMap<Action, Denial>,
+}
+
 #[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Index {
     FromStart(u64), // Absolute index
     FromEnd(u64),   // Relative index - start counting from the end
 }
 
+impl Index {
+    /// Resolves this index into an absolute position in a list of `len` items.
+    ///
+    /// Valid positions are `0..=len`, so both `FromStart(len)` and `FromEnd(0)` point just past
+    /// the last item. Returns `Error::InvalidIndexRange` if the index falls outside the list.
+    pub fn resolve(self, len: u64) -> Result<u64> {
+        match self {
+            Index::FromStart(idx) if idx <= len => Ok(idx),
+            Index::FromEnd(idx) => len.checked_sub(idx).ok_or(Error::InvalidIndexRange),
+            Index::FromStart(_) => Err(Error::InvalidIndexRange),
+        }
+    }
+}
+
+// Resolves a pair of indices into a range of positions in a list of `len` items.
+fn resolve_range(start: Index, end: Index, len: usize) -> Result<Range<usize>> {
+    let start = start.resolve(len as u64)? as usize;
+    let end = end.resolve(len as u64)? as usize;
+    if end < start {
+        return Err(Error::InvalidIndexRange);
+    }
+    Ok(start..end)
+}
+
 // Set of data, owners, permissions Indices.
 #[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Indices {
@@ -151,6 +207,28 @@ impl Address {
         Address::UnpubUnseq { name, tag }
     }
 
+    pub fn is_pub(&self) -> bool {
+        match self {
+            Address::PubSeq { .. } | Address::PubUnseq { .. } => true,
+            Address::UnpubSeq { .. } | Address::UnpubUnseq { .. } => false,
+        }
+    }
+
+    pub fn is_unpub(&self) -> bool {
+        !self.is_pub()
+    }
+
+    pub fn is_seq(&self) -> bool {
+        match self {
+            Address::PubSeq { .. } | Address::UnpubSeq { .. } => true,
+            Address::PubUnseq { .. } | Address::UnpubUnseq { .. } => false,
+        }
+    }
+
+    pub fn is_unseq(&self) -> bool {
+        !self.is_seq()
+    }
+
     pub fn name(&self) -> &XorName {
         match self {
             Address::PubSeq { ref name, .. }
@@ -180,8 +258,18 @@ impl Address {
     }
 }
 
-pub trait Permissions {
-    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()>;
+pub trait Permissions: Serialize {
+    /// Checks whether `requester`, who doesn't satisfy the owners, may perform `action`.
+    fn check_action(&self, requester: PublicKey, action: Action) -> result::Result<(), Denial>;
+
+    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
+        self.check_action(requester, action)
+            .map_err(|denial| match denial {
+                Denial::NoEntry => Error::NoSuchEntry,
+                _ => Error::AccessDenied,
+            })
+    }
+
     fn data_index(&self) -> u64;
     fn owner_entry_index(&self) -> u64;
     fn check_permissions_for_key(&self, requester: PublicKey, request: &Request) -> Result<()>;
@@ -203,16 +291,11 @@ impl UnpubPermissions {
 }
 
 impl Permissions for UnpubPermissions {
-    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
+    fn check_action(&self, requester: PublicKey, action: Action) -> result::Result<(), Denial> {
         match self.permissions.get(&requester) {
-            Some(perms) => {
-                if perms.is_allowed(action) {
-                    Ok(())
-                } else {
-                    Err(Error::AccessDenied)
-                }
-            }
-            None => Err(Error::NoSuchEntry),
+            Some(perms) if perms.is_allowed(action) => Ok(()),
+            Some(_) => Err(Denial::NotAllowed),
+            None => Err(Denial::NoEntry),
         }
     }
 
@@ -231,9 +314,12 @@ impl Permissions for UnpubPermissions {
             | Request::GetADataRange { .. }
             | Request::GetADataIndices(..)
             | Request::GetADataLastEntry(..)
+            | Request::GetADataValue { .. }
             | Request::GetADataPermissions { .. }
             | Request::GetUnpubADataUserPermissions { .. }
-            | Request::GetADataOwners { .. } => self.is_action_allowed(requester, Action::Read),
+            | Request::GetADataOwners { .. }
+            | Request::SubscribeAData { .. }
+            | Request::UnsubscribeAData(..) => self.is_action_allowed(requester, Action::Read),
             Request::AddUnpubADataPermissions { .. } => {
                 self.is_action_allowed(requester, Action::ManagePermissions)
             }
@@ -269,19 +355,19 @@ impl PubPermissions {
 }
 
 impl Permissions for PubPermissions {
-    fn is_action_allowed(&self, requester: PublicKey, action: Action) -> Result<()> {
+    fn check_action(&self, requester: PublicKey, action: Action) -> result::Result<(), Denial> {
+        // It's published data, so anyone can read it.
+        if let Action::Read = action {
+            return Ok(());
+        }
         match self.permissions.get(&User::Key(requester)) {
-            Some(perms) => {
-                if perms
-                    .is_allowed(action)
-                    .unwrap_or_else(|| self.check_anyone_permissions(action))
-                {
-                    Ok(())
-                } else {
-                    Err(Error::AccessDenied)
-                }
-            }
-            None => Err(Error::NoSuchEntry),
+            Some(perms) => match perms.is_allowed(action) {
+                Some(true) => Ok(()),
+                Some(false) => Err(Denial::NotAllowed),
+                None if self.check_anyone_permissions(action) => Ok(()),
+                None => Err(Denial::NotAllowedForAnyone),
+            },
+            None => Err(Denial::NoEntry),
         }
     }
 
@@ -300,9 +386,12 @@ impl Permissions for PubPermissions {
             | Request::GetADataRange { .. }
             | Request::GetADataIndices(..)
             | Request::GetADataLastEntry(..)
+            | Request::GetADataValue { .. }
             | Request::GetADataPermissions { .. }
             | Request::GetPubADataUserPermissions { .. }
-            | Request::GetADataOwners { .. } => Ok(()),
+            | Request::GetADataOwners { .. }
+            | Request::SubscribeAData { .. }
+            | Request::UnsubscribeAData(..) => Ok(()),
             Request::AddPubADataPermissions { .. } => {
                 self.is_action_allowed(requester, Action::ManagePermissions)
             }
@@ -317,21 +406,176 @@ impl Permissions for PubPermissions {
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Owner {
-    pub public_key: PublicKey,
+    /// The owners and their signing policy
+    pub owners: OwnerSet,
     /// The current index of the data when this ownership change happened
     pub data_index: u64,
     /// The current index of the permissions when this ownership change happened
     pub permissions_index: u64,
 }
 
-#[derive(Clone, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash)]
+#[derive(Clone, Serialize, PartialEq, PartialOrd, Ord, Eq, Hash)]
 struct AppendOnly<P: Permissions> {
     address: Address,
     data: Entries,
     permissions: Vec<P>,
-    // This is the history of owners, with each entry representing an owner.  Each single owner
-    // could represent an individual user, or a group of users, depending on the `PublicKey` type.
+    // This is the history of owners, with each entry representing a set of owners.  Each set
+    // could represent an individual user, or a group of users with a threshold signing policy.
     owners: Vec<Owner>,
+    // Maps each key to the indices it appears at, in ascending order. Not serialised, but
+    // rebuilt from `data` on deserialisation.
+    #[serde(skip)]
+    key_index: BTreeMap<Vec<u8>, Vec<u64>>,
+}
+
+impl<'de, P> Deserialize<'de> for AppendOnly<P>
+where
+    P: Permissions + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Fields<P> {
+            address: Address,
+            data: Entries,
+            permissions: Vec<P>,
+            owners: Vec<Owner>,
+        }
+
+        let fields: Fields<P> = Deserialize::deserialize(deserializer)?;
+        let mut data = AppendOnly::new(fields.address);
+        data.permissions = fields.permissions;
+        data.owners = fields.owners;
+        data.index_entries(fields.data);
+        Ok(data)
+    }
+}
+
+impl<P: Permissions> AppendOnly<P> {
+    fn new(address: Address) -> Self {
+        AppendOnly {
+            address,
+            data: Vec::new(),
+            permissions: Vec::new(),
+            owners: Vec::new(),
+            key_index: BTreeMap::new(),
+        }
+    }
+
+    // Adds the entries to `data` and `key_index`, without checking the limits.
+    fn index_entries<I: IntoIterator<Item = (Vec<u8>, Vec<u8>)>>(&mut self, entries: I) {
+        for (key, value) in entries {
+            let index = self.data.len() as u64;
+            self.key_index.entry(key.clone()).or_default().push(index);
+            self.data.push((key, value));
+        }
+    }
+
+    fn serialised_size(&self) -> u64 {
+        serialized_size(self).unwrap_or(u64::MAX)
+    }
+
+    fn check_limits(&self, limits: &Limits) -> Result<()> {
+        if self.permissions.len() as u64 > limits.max_permissions
+            || self.owners.len() as u64 > limits.max_owners
+        {
+            return Err(Error::TooManyEntries);
+        }
+        if self.data.iter().any(|(key, value)| {
+            key.len() as u64 > limits.max_key_size || value.len() as u64 > limits.max_value_size
+        }) {
+            return Err(Error::ExceededSize);
+        }
+        if self.serialised_size() > limits.max_size {
+            return Err(Error::ExceededSize);
+        }
+        Ok(())
+    }
+
+    // Checks that every owners entry has a valid signing policy, and that the owners and
+    // permissions histories could have been built by appending to the data, as `append_owner` and
+    // `append_permissions` do.
+    fn verify_histories(&self) -> Result<()> {
+        if let Some(index) = self
+            .owners
+            .iter()
+            .position(|owner| owner.owners.validate().is_err())
+        {
+            return Err(Error::InvalidData(VerificationError::InvalidOwnersEntry(
+                index as u64,
+            )));
+        }
+        let len = self.data.len() as u64;
+        let owners: Vec<_> = self
+            .owners
+            .iter()
+            .map(|owner| (owner.data_index, owner.permissions_index))
+            .collect();
+        let permissions: Vec<_> = self
+            .permissions
+            .iter()
+            .map(|permissions| (permissions.data_index(), permissions.owner_entry_index()))
+            .collect();
+        if let Some(index) = first_inconsistent_entry(&owners, &permissions, len) {
+            return Err(Error::InvalidData(VerificationError::InvalidOwnersEntry(
+                index,
+            )));
+        }
+        if let Some(index) = first_inconsistent_entry(&permissions, &owners, len) {
+            return Err(Error::InvalidData(
+                VerificationError::InvalidPermissionsEntry(index),
+            ));
+        }
+        Ok(())
+    }
+
+    // Appends the entries, unless that would exceed `limits`.
+    fn append(&mut self, entries: &[(Vec<u8>, Vec<u8>)], limits: &Limits) -> Result<()> {
+        let len = self.data.len();
+        self.data.extend(entries.iter().cloned());
+        let result = self.check_limits(limits);
+        self.data.truncate(len);
+        if result.is_ok() {
+            self.index_entries(entries.iter().cloned());
+        }
+        result
+    }
+}
+
+// Every entry of `history` records the length of the data and of the `other` history at the time it
+// was appended. Returns the index of the first entry which can't have been appended in order with
+// the entries of `other`.
+fn first_inconsistent_entry(
+    history: &[(u64, u64)],
+    other: &[(u64, u64)],
+    data_len: u64,
+) -> Option<u64> {
+    let mut previous = (0, 0);
+    for (index, &(data_index, other_index)) in history.iter().enumerate() {
+        let index = index as u64;
+        if data_index < previous.0
+            || other_index < previous.1
+            || data_index > data_len
+            || other_index > other.len() as u64
+        {
+            return Some(index);
+        }
+        // The entry of `other` appended right before this one, and the one appended right after.
+        if let Some(&(other_data_index, position)) = (other_index as usize)
+            .checked_sub(1)
+            .and_then(|other_index| other.get(other_index))
+        {
+            if other_data_index > data_index || position > index {
+                return Some(index);
+            }
+        }
+        if let Some(&(other_data_index, position)) = other.get(other_index as usize) {
+            if other_data_index < data_index || position <= index {
+                return Some(index);
+            }
+        }
+        previous = (data_index, other_index);
+    }
+    None
 }
 
 /// Common methods for all `AppendOnlyData` flavours.
@@ -339,14 +583,20 @@ pub trait AppendOnlyData<P> {
     // /// Get a list of permissions for the provided user from the last entry in the permissions list.
     // fn user_permissions(&self, user: &User) -> Result<&PubPermissionSet>;
 
-    /// Return a value for the given key (if it is present).
+    /// Return the value of the first entry with the given key (if it is present).
     fn get(&self, key: &[u8]) -> Option<&Vec<u8>>;
 
+    /// Return the value of the last entry with the given key (if it is present).
+    fn get_latest(&self, key: &[u8]) -> Option<&Vec<u8>>;
+
+    /// Return the indices of all entries with the given key, in ascending order.
+    fn get_all(&self, key: &[u8]) -> Vec<u64>;
+
     /// Return the last entry in the Data (if it is present).
     fn last_entry(&self) -> Option<(Vec<u8>, Vec<u8>)>;
 
     /// Get a list of keys and values with the given indices.
-    fn in_range(&self, start: Index, end: Index) -> Option<Entries>;
+    fn in_range(&self, start: Index, end: Index) -> Result<Entries>;
 
     /// Return all entries.
     fn entries(&self) -> &Entries;
@@ -371,11 +621,11 @@ pub trait AppendOnlyData<P> {
 
     /// Get a complete list of permissions from the entry in the permissions list at the specified
     /// index.
-    fn permissions_range(&self, start: Index, end: Index) -> Option<&[P]>;
+    fn permissions_range(&self, start: Index, end: Index) -> Result<&[P]>;
 
     /// Add a new permissions entry.
     /// The `Permissions` struct should contain valid indices.
-    fn append_permissions(&mut self, permissions: P) -> Result<()>;
+    fn append_permissions(&mut self, permissions: P, limits: &Limits) -> Result<()>;
 
     /// Fetch perms at index.
     fn fetch_permissions_at_index(&self, perm_index: u64) -> Option<&P>;
@@ -384,16 +634,22 @@ pub trait AppendOnlyData<P> {
     fn fetch_owner_at_index(&self, owners_index: u64) -> Option<&Owner>;
 
     /// Get a complete list of owners from the entry in the permissions list at the specified index.
-    fn owners_range(&self, start: Index, end: Index) -> Option<&[Owner]>;
+    fn owners_range(&self, start: Index, end: Index) -> Result<&[Owner]>;
 
     /// Add a new owner entry.
-    fn append_owner(&mut self, owner: Owner) -> Result<()>;
+    fn append_owner(&mut self, owner: Owner, limits: &Limits) -> Result<()>;
+
+    /// Return the size of the serialised data.
+    fn serialised_size(&self) -> u64;
+
+    /// Check the size of the entries and histories against `limits`.
+    fn check_limits(&self, limits: &Limits) -> Result<()>;
 }
 
 /// Common methods for published and unpublished unsequenced `AppendOnlyData`.
 pub trait UnseqAppendOnly {
     /// Append new entries.
-    fn append(&mut self, entries: &[(Vec<u8>, Vec<u8>)]) -> Result<()>;
+    fn append(&mut self, entries: &[(Vec<u8>, Vec<u8>)], limits: &Limits) -> Result<()>;
 }
 
 /// Common methods for published and unpublished sequenced `AppendOnlyData`.
@@ -402,7 +658,12 @@ pub trait SeqAppendOnly {
     ///
     /// If the specified `last_entries_index` does not match the last recorded entries index, an
     /// error will be returned.
-    fn append(&mut self, entries: &[(Vec<u8>, Vec<u8>)], last_entries_index: u64) -> Result<()>;
+    fn append(
+        &mut self,
+        entries: &[(Vec<u8>, Vec<u8>)],
+        last_entries_index: u64,
+        limits: &Limits,
+    ) -> Result<()>;
 }
 
 macro_rules! impl_appendable_data {
@@ -420,9 +681,7 @@ macro_rules! impl_appendable_data {
             P: Permissions + std::hash::Hash + Clone,
         {
             pub fn shell(&self, index: u64) -> Result<Self> {
-                if index > self.entry_index() {
-                    return Err(Error::NoSuchEntry);
-                }
+                let index = Index::FromStart(index).resolve(self.entry_index())?;
 
                 let permissions = self
                     .inner
@@ -446,6 +705,7 @@ macro_rules! impl_appendable_data {
                         data: Vec::new(),
                         permissions,
                         owners,
+                        key_index: BTreeMap::new(),
                     },
                 })
             }
@@ -480,10 +740,17 @@ macro_rules! impl_appendable_data {
             }
 
             fn get(&self, key: &[u8]) -> Option<&Vec<u8>> {
-                self.inner
-                    .data
-                    .iter()
-                    .find_map(|(k, v)| if k.as_slice() == key { Some(v) } else { None })
+                let index = self.inner.key_index.get(key)?.first()?;
+                self.inner.data.get(*index as usize).map(|(_, value)| value)
+            }
+
+            fn get_latest(&self, key: &[u8]) -> Option<&Vec<u8>> {
+                let index = self.inner.key_index.get(key)?.last()?;
+                self.inner.data.get(*index as usize).map(|(_, value)| value)
+            }
+
+            fn get_all(&self, key: &[u8]) -> Vec<u64> {
+                self.inner.key_index.get(key).cloned().unwrap_or_default()
             }
 
             fn last_entry(&self) -> Option<(Vec<u8>, Vec<u8>)> {
@@ -501,84 +768,26 @@ macro_rules! impl_appendable_data {
                 self.inner.owners.get(owners_index as usize)
             }
 
-            fn in_range(&self, start: Index, end: Index) -> Option<Entries> {
-                let idx_start = match start {
-                    Index::FromStart(idx) => idx as usize,
-                    Index::FromEnd(idx) => self.inner.data.len() - (idx as usize),
-                };
-                let idx_end = match end {
-                    Index::FromStart(idx) => idx as usize,
-                    Index::FromEnd(idx) => self.inner.data.len() - (idx as usize),
-                };
-
-                // Check bounds
-                if idx_start > self.inner.data.len() {
-                    return None;
-                }
-                if idx_end < idx_start {
-                    return None;
-                }
-                if idx_start == idx_end {
-                    // Return empty slice because range len is 0
-                    return Some(Vec::new());
-                }
-
-                Some(self.inner.data[idx_start..idx_end].to_vec())
+            fn in_range(&self, start: Index, end: Index) -> Result<Entries> {
+                let range = resolve_range(start, end, self.inner.data.len())?;
+                Ok(self.inner.data[range].to_vec())
             }
 
             fn entries(&self) -> &Entries {
                 &self.inner.data
             }
 
-            fn permissions_range(&self, start: Index, end: Index) -> Option<&[P]> {
-                // Check bounds
-                let idx_start = match start {
-                    Index::FromStart(idx) => idx as usize,
-                    Index::FromEnd(idx) => self.inner.permissions.len() - (idx as usize),
-                };
-                let idx_end = match end {
-                    Index::FromStart(idx) => idx as usize,
-                    Index::FromEnd(idx) => self.inner.permissions.len() - (idx as usize),
-                };
-                if idx_start > self.inner.permissions.len() {
-                    return None;
-                }
-                if idx_end < idx_start {
-                    return None;
-                }
-                if idx_start == idx_end {
-                    // Empty slice
-                    return Some(&[]);
-                }
-
-                Some(&self.inner.permissions[idx_start..idx_end])
-            }
-
-            fn owners_range(&self, start: Index, end: Index) -> Option<&[Owner]> {
-                // Check bounds
-                let idx_start = match start {
-                    Index::FromStart(idx) => idx as usize,
-                    Index::FromEnd(idx) => self.inner.owners.len() - (idx as usize),
-                };
-                let idx_end = match end {
-                    Index::FromStart(idx) => idx as usize,
-                    Index::FromEnd(idx) => self.inner.owners.len() - (idx as usize),
-                };
-                if idx_start > self.inner.owners.len() {
-                    return None;
-                }
-                if idx_end < idx_start {
-                    return None;
-                }
-                if idx_start == idx_end {
-                    // Empty slice
-                    return Some(&[]);
-                }
+            fn permissions_range(&self, start: Index, end: Index) -> Result<&[P]> {
+                let range = resolve_range(start, end, self.inner.permissions.len())?;
+                Ok(&self.inner.permissions[range])
+            }
 
-                Some(&self.inner.owners[idx_start..idx_end])
+            fn owners_range(&self, start: Index, end: Index) -> Result<&[Owner]> {
+                let range = resolve_range(start, end, self.inner.owners.len())?;
+                Ok(&self.inner.owners[range])
             }
 
-            fn append_permissions(&mut self, permissions: P) -> Result<()> {
+            fn append_permissions(&mut self, permissions: P, limits: &Limits) -> Result<()> {
                 if permissions.data_index() != self.entry_index() {
                     return Err(Error::InvalidSuccessor(self.entry_index()));
                 }
@@ -586,10 +795,14 @@ macro_rules! impl_appendable_data {
                     return Err(Error::InvalidOwnersSuccessor(self.owners_index()));
                 }
                 self.inner.permissions.push(permissions);
-                Ok(())
+                let result = self.inner.check_limits(limits);
+                if result.is_err() {
+                    let _ = self.inner.permissions.pop();
+                }
+                result
             }
 
-            fn append_owner(&mut self, owner: Owner) -> Result<()> {
+            fn append_owner(&mut self, owner: Owner, limits: &Limits) -> Result<()> {
                 if owner.data_index != self.entry_index() {
                     return Err(Error::InvalidSuccessor(self.entry_index()));
                 }
@@ -597,7 +810,19 @@ macro_rules! impl_appendable_data {
                     return Err(Error::InvalidPermissionsSuccessor(self.permissions_index()));
                 }
                 self.inner.owners.push(owner);
-                Ok(())
+                let result = self.inner.check_limits(limits);
+                if result.is_err() {
+                    let _ = self.inner.owners.pop();
+                }
+                result
+            }
+
+            fn serialised_size(&self) -> u64 {
+                self.inner.serialised_size()
+            }
+
+            fn check_limits(&self, limits: &Limits) -> Result<()> {
+                self.inner.check_limits(limits)
             }
         }
     };
@@ -609,12 +834,7 @@ impl_appendable_data!(UnseqAppendOnlyData);
 impl SeqAppendOnlyData<PubPermissions> {
     pub fn new(name: XorName, tag: u64) -> Self {
         Self {
-            inner: AppendOnly {
-                address: Address::new_pub_seq(name, tag),
-                data: Vec::new(),
-                permissions: Vec::new(),
-                owners: Vec::new(),
-            },
+            inner: AppendOnly::new(Address::new_pub_seq(name, tag)),
         }
     }
 }
@@ -622,12 +842,7 @@ impl SeqAppendOnlyData<PubPermissions> {
 impl UnseqAppendOnlyData<PubPermissions> {
     pub fn new(name: XorName, tag: u64) -> Self {
         Self {
-            inner: AppendOnly {
-                address: Address::new_pub_unseq(name, tag),
-                data: Vec::new(),
-                permissions: Vec::new(),
-                owners: Vec::new(),
-            },
+            inner: AppendOnly::new(Address::new_pub_unseq(name, tag)),
         }
     }
 }
@@ -635,12 +850,7 @@ impl UnseqAppendOnlyData<PubPermissions> {
 impl SeqAppendOnlyData<UnpubPermissions> {
     pub fn new(name: XorName, tag: u64) -> Self {
         Self {
-            inner: AppendOnly {
-                address: Address::new_unpub_seq(name, tag),
-                data: Vec::new(),
-                permissions: Vec::new(),
-                owners: Vec::new(),
-            },
+            inner: AppendOnly::new(Address::new_unpub_seq(name, tag)),
         }
     }
 }
@@ -648,12 +858,7 @@ impl SeqAppendOnlyData<UnpubPermissions> {
 impl UnseqAppendOnlyData<UnpubPermissions> {
     pub fn new(name: XorName, tag: u64) -> Self {
         Self {
-            inner: AppendOnly {
-                address: Address::new_unpub_unseq(name, tag),
-                data: Vec::new(),
-                permissions: Vec::new(),
-                owners: Vec::new(),
-            },
+            inner: AppendOnly::new(Address::new_unpub_unseq(name, tag)),
         }
     }
 }
@@ -662,12 +867,16 @@ impl<P> SeqAppendOnly for SeqAppendOnlyData<P>
 where
     P: Permissions + std::hash::Hash + Clone,
 {
-    fn append(&mut self, entries: &[(Vec<u8>, Vec<u8>)], last_entries_index: u64) -> Result<()> {
+    fn append(
+        &mut self,
+        entries: &[(Vec<u8>, Vec<u8>)],
+        last_entries_index: u64,
+        limits: &Limits,
+    ) -> Result<()> {
         if last_entries_index != self.inner.data.len() as u64 {
             return Err(Error::InvalidSuccessor(self.inner.data.len() as u64));
         }
-        self.inner.data.extend(entries.iter().cloned());
-        Ok(())
+        self.inner.append(entries, limits)
     }
 }
 
@@ -675,30 +884,74 @@ impl<P> UnseqAppendOnly for UnseqAppendOnlyData<P>
 where
     P: Permissions + std::hash::Hash + Clone,
 {
-    fn append(&mut self, entries: &[(Vec<u8>, Vec<u8>)]) -> Result<()> {
-        self.inner.data.extend(entries.iter().cloned());
-        Ok(())
+    fn append(&mut self, entries: &[(Vec<u8>, Vec<u8>)], limits: &Limits) -> Result<()> {
+        self.inner.append(entries, limits)
     }
 }
 
 macro_rules! check_perm {
-    ($data: ident, $requester: ident, $request: ident) => {
+    ($data: ident, $requester: ident, $co_signers: ident, $request: ident) => {
         if $data
-            .fetch_owner_at_index($data.owners_index() - 1)
+            .owners_index()
+            .checked_sub(1)
+            .and_then(|idx| $data.fetch_owner_at_index(idx))
             .ok_or_else(|| Error::NoSuchData)?
-            .public_key
-            == $requester
+            .owners
+            .is_satisfied_by(iter::once(&$requester).chain(&$co_signers))
         {
             Ok(())
         } else {
             $data
-                .fetch_permissions_at_index($data.permissions_index() - 1)
-                .ok_or_else(|| Error::NoSuchData)?
+                .permissions_index()
+                .checked_sub(1)
+                .and_then(|idx| $data.fetch_permissions_at_index(idx))
+                .ok_or_else(|| Error::AccessDenied)?
                 .check_permissions_for_key($requester, $request)
         }
     };
 }
 
+// Checks every action for `key` against the current owners and permissions of `data`, the same way
+// `check_perm!` does.
+fn effective_permissions<P, D>(data: &D, key: PublicKey) -> EffectivePermissions
+where
+    P: Permissions,
+    D: AppendOnlyData<P>,
+{
+    let owner = data
+        .owners_index()
+        .checked_sub(1)
+        .and_then(|idx| data.fetch_owner_at_index(idx));
+    let is_owner = match owner {
+        Some(owner) => owner.owners.is_satisfied_by(&[key]),
+        None => false,
+    };
+    let permissions = data
+        .permissions_index()
+        .checked_sub(1)
+        .and_then(|idx| data.fetch_permissions_at_index(idx));
+
+    let mut result = EffectivePermissions::default();
+    for action in &[Action::Read, Action::Append, Action::ManagePermissions] {
+        let check = if is_owner {
+            Ok(())
+        } else {
+            permissions
+                .ok_or(Denial::NoPermissions)
+                .and_then(|permissions| permissions.check_action(key, *action))
+        };
+        match check {
+            Ok(()) => {
+                let _ = result.allowed.insert(*action);
+            }
+            Err(denial) => {
+                let _ = result.denied.insert(*action, denial);
+            }
+        }
+    }
+    result
+}
+
 macro_rules! indices {
     ($data: ident) => {
         Ok(Indices::new(
@@ -719,14 +972,73 @@ pub enum AData {
 
 impl AData {
     pub fn check_permission(&self, request: &Request, requester: PublicKey) -> Result<()> {
+        // Deletions are approved by the owners' signatures in the request, whoever sends it.
+        if let Request::DeleteAData(proof) = request.inner() {
+            return self.check_deletion(proof);
+        }
+        // The co-signatures are expected to be verified by the caller, like the requester's own
+        // signature.
+        let co_signers = request.co_signers();
+        let request = request.inner();
+        match self {
+            AData::PubSeq(data) => check_perm!(data, requester, co_signers, request),
+            AData::PubUnseq(data) => check_perm!(data, requester, co_signers, request),
+            AData::UnpubSeq(data) => check_perm!(data, requester, co_signers, request),
+            AData::UnpubUnseq(data) => check_perm!(data, requester, co_signers, request),
+        }
+    }
+
+    /// Returns the actions `key` can perform on the data, and why it can't perform the others.
+    pub fn effective_permissions(&self, key: PublicKey) -> EffectivePermissions {
+        match self {
+            AData::PubSeq(data) => effective_permissions(data, key),
+            AData::PubUnseq(data) => effective_permissions(data, key),
+            AData::UnpubSeq(data) => effective_permissions(data, key),
+            AData::UnpubUnseq(data) => effective_permissions(data, key),
+        }
+    }
+
+    pub fn check_limits(&self, limits: &Limits) -> Result<()> {
         match self {
-            AData::PubSeq(data) => check_perm!(data, requester, request),
-            AData::PubUnseq(data) => check_perm!(data, requester, request),
-            AData::UnpubSeq(data) => check_perm!(data, requester, request),
-            AData::UnpubUnseq(data) => check_perm!(data, requester, request),
+            AData::PubSeq(data) => data.check_limits(limits),
+            AData::PubUnseq(data) => data.check_limits(limits),
+            AData::UnpubSeq(data) => data.check_limits(limits),
+            AData::UnpubUnseq(data) => data.check_limits(limits),
         }
     }
 
+    /// Checks that `signatures` over `data` satisfy the signing policy of the current owners.
+    pub fn check_owner_signatures(
+        &self,
+        data: &[u8],
+        signatures: &BTreeMap<PublicKey, Signature>,
+    ) -> Result<()> {
+        self.owners_index()
+            .checked_sub(1)
+            .and_then(|idx| self.owners(idx))
+            .ok_or(Error::NoSuchData)?
+            .owners
+            .verify(data, signatures)
+    }
+
+    /// Checks that `proof` approves deleting this data, signed by its current owners at the
+    /// current owners index.
+    pub fn check_deletion(&self, proof: &DeletionProof<Address>) -> Result<()> {
+        if proof.address != *self.address() {
+            return Err(Error::AccessDenied);
+        }
+        if self.address().is_pub() {
+            return Err(Error::InvalidOperation);
+        }
+        if proof.version != self.owners_index() {
+            return Err(Error::InvalidOwnersSuccessor(self.owners_index()));
+        }
+        self.check_owner_signatures(
+            &deletion_payload(&proof.address, proof.version),
+            &proof.signatures,
+        )
+    }
+
     pub fn address(&self) -> &Address {
         match self {
             AData::PubSeq(data) => data.address(),
@@ -753,6 +1065,15 @@ impl AData {
         }
     }
 
+    pub fn entry_index(&self) -> u64 {
+        match self {
+            AData::PubSeq(data) => data.entry_index(),
+            AData::PubUnseq(data) => data.entry_index(),
+            AData::UnpubSeq(data) => data.entry_index(),
+            AData::UnpubUnseq(data) => data.entry_index(),
+        }
+    }
+
     pub fn owners_index(&self) -> u64 {
         match self {
             AData::PubSeq(data) => data.owners_index(),
@@ -762,7 +1083,7 @@ impl AData {
         }
     }
 
-    pub fn in_range(&self, start: Index, end: Index) -> Option<Entries> {
+    pub fn in_range(&self, start: Index, end: Index) -> Result<Entries> {
         match self {
             AData::PubSeq(data) => data.in_range(start, end),
             AData::PubUnseq(data) => data.in_range(start, end),
@@ -780,6 +1101,24 @@ impl AData {
         }
     }
 
+    pub fn get_latest(&self, key: &[u8]) -> Option<&Vec<u8>> {
+        match self {
+            AData::PubSeq(data) => data.get_latest(key),
+            AData::PubUnseq(data) => data.get_latest(key),
+            AData::UnpubSeq(data) => data.get_latest(key),
+            AData::UnpubUnseq(data) => data.get_latest(key),
+        }
+    }
+
+    pub fn get_all(&self, key: &[u8]) -> Vec<u64> {
+        match self {
+            AData::PubSeq(data) => data.get_all(key),
+            AData::PubUnseq(data) => data.get_all(key),
+            AData::UnpubSeq(data) => data.get_all(key),
+            AData::UnpubUnseq(data) => data.get_all(key),
+        }
+    }
+
     pub fn last_entry(&self) -> Option<(Vec<u8>, Vec<u8>)> {
         match self {
             AData::PubSeq(data) => data.last_entry(),
@@ -830,6 +1169,42 @@ impl AData {
     }
 }
 
+impl Verifiable for AData {
+    type Address = Address;
+
+    /// Checks the kind, name and tag of the data, the signing policy of every owners entry, and
+    /// that its owners and permissions histories are consistent with each other and with its
+    /// entries.
+    fn verify(&self, address: &Address) -> Result<()> {
+        let actual = self.address();
+        let consistent_kind = match (self, actual) {
+            (AData::PubSeq(_), Address::PubSeq { .. })
+            | (AData::PubUnseq(_), Address::PubUnseq { .. })
+            | (AData::UnpubSeq(_), Address::UnpubSeq { .. })
+            | (AData::UnpubUnseq(_), Address::UnpubUnseq { .. }) => true,
+            _ => false,
+        };
+        if !consistent_kind
+            || actual.is_pub() != address.is_pub()
+            || actual.is_seq() != address.is_seq()
+        {
+            return Err(Error::InvalidData(VerificationError::KindMismatch));
+        }
+        if actual.name() != address.name() {
+            return Err(Error::InvalidData(VerificationError::NameMismatch));
+        }
+        if actual.tag() != address.tag() {
+            return Err(Error::InvalidData(VerificationError::TagMismatch));
+        }
+        match self {
+            AData::PubSeq(data) => data.inner.verify_histories(),
+            AData::PubUnseq(data) => data.inner.verify_histories(),
+            AData::UnpubSeq(data) => data.inner.verify_histories(),
+            AData::UnpubUnseq(data) => data.inner.verify_histories(),
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash)]
 pub struct AppendOperation {
     // Address of an AppendOnlyData object on the network.
@@ -838,9 +1213,31 @@ pub struct AppendOperation {
     pub values: Entries,
 }
 
+/// Entries appended to an AppendOnlyData, as delivered to its subscribers.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash)]
+pub struct Event {
+    /// Address of the AppendOnlyData the entries were appended to.
+    pub address: Address,
+    /// Index of the first entry in `entries`. The rest follow consecutively.
+    pub index: u64,
+    /// The appended entries, in order.
+    pub entries: Entries,
+}
+
+impl Event {
+    /// Returns the entries together with their indices.
+    pub fn indexed_entries(&self) -> impl Iterator<Item = (u64, &(Vec<u8>, Vec<u8>))> {
+        (self.index..).zip(self.entries.iter())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        MAX_APPEND_ONLY_DATA_OWNERS, MAX_DATA_SIZE_IN_BYTES, MAX_ENTRY_KEY_SIZE_IN_BYTES,
+        MAX_ENTRY_VALUE_SIZE_IN_BYTES,
+    };
     use threshold_crypto::SecretKey;
     use unwrap::unwrap;
 
@@ -849,11 +1246,14 @@ mod tests {
         let mut data = SeqAppendOnlyData::<UnpubPermissions>::new(XorName([1; 32]), 10000);
 
         // Append the first permission set with correct indices - should pass.
-        let res = data.append_permissions(UnpubPermissions {
-            permissions: BTreeMap::new(),
-            data_index: 0,
-            owner_entry_index: 0,
-        });
+        let res = data.append_permissions(
+            UnpubPermissions {
+                permissions: BTreeMap::new(),
+                data_index: 0,
+                owner_entry_index: 0,
+            },
+            &Limits::default(),
+        );
 
         match res {
             Ok(()) => (),
@@ -867,11 +1267,14 @@ mod tests {
         );
 
         // Append another permissions entry with incorrect indices - should fail.
-        let res = data.append_permissions(UnpubPermissions {
-            permissions: BTreeMap::new(),
-            data_index: 64,
-            owner_entry_index: 0,
-        });
+        let res = data.append_permissions(
+            UnpubPermissions {
+                permissions: BTreeMap::new(),
+                data_index: 64,
+                owner_entry_index: 0,
+            },
+            &Limits::default(),
+        );
 
         match res {
             Err(_) => (),
@@ -885,6 +1288,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn limits() {
+        let owner_pk = PublicKey::Bls(SecretKey::random().public_key());
+        let mut data = UnseqAppendOnlyData::<PubPermissions>::new(XorName([1; 32]), 10000);
+
+        // The owners history is capped.
+        for _ in 0..MAX_APPEND_ONLY_DATA_OWNERS {
+            unwrap!(data.append_owner(
+                Owner {
+                    owners: owner_pk.into(),
+                    data_index: 0,
+                    permissions_index: 0,
+                },
+                &Limits::default()
+            ));
+        }
+        assert_eq!(
+            data.append_owner(
+                Owner {
+                    owners: owner_pk.into(),
+                    data_index: 0,
+                    permissions_index: 0,
+                },
+                &Limits::default()
+            ),
+            Err(Error::TooManyEntries)
+        );
+        assert_eq!(data.owners_index(), MAX_APPEND_ONLY_DATA_OWNERS);
+
+        let big_key = vec![0; MAX_ENTRY_KEY_SIZE_IN_BYTES as usize + 1];
+        assert_eq!(
+            data.append(&[(big_key, Vec::new())], &Limits::default()),
+            Err(Error::ExceededSize)
+        );
+
+        // Entries within the per-entry limit, but exceeding the total size together.
+        let value = vec![0; MAX_ENTRY_VALUE_SIZE_IN_BYTES as usize];
+        let count = MAX_DATA_SIZE_IN_BYTES / MAX_ENTRY_VALUE_SIZE_IN_BYTES;
+        let entries: Vec<_> = (0..count)
+            .map(|i| (i.to_string().into_bytes(), value.clone()))
+            .collect();
+        unwrap!(data.append(&entries, &Limits::default()));
+        assert_eq!(
+            data.append(&[(b"last".to_vec(), value)], &Limits::default()),
+            Err(Error::ExceededSize)
+        );
+        assert_eq!(data.entry_index(), count);
+
+        // Tighter limits are honoured.
+        let mut data = UnseqAppendOnlyData::<PubPermissions>::new(XorName([1; 32]), 10000);
+        let limits = Limits {
+            max_value_size: 1,
+            max_permissions: 0,
+            ..Limits::default()
+        };
+        assert_eq!(
+            data.append(&[(b"a".to_vec(), b"12".to_vec())], &limits),
+            Err(Error::ExceededSize)
+        );
+        unwrap!(data.append(&[(b"a".to_vec(), b"1".to_vec())], &limits));
+        assert_eq!(
+            data.append_permissions(
+                PubPermissions {
+                    permissions: BTreeMap::new(),
+                    data_index: 1,
+                    owner_entry_index: 0,
+                },
+                &limits
+            ),
+            Err(Error::TooManyEntries)
+        );
+        assert_eq!(data.permissions_index(), 0);
+    }
+
+    #[test]
+    fn key_index() {
+        let mut data = SeqAppendOnlyData::<PubPermissions>::new(XorName([1; 32]), 10000);
+        unwrap!(data.append(
+            &[
+                (b"a".to_vec(), b"1".to_vec()),
+                (b"b".to_vec(), b"2".to_vec()),
+                (b"a".to_vec(), b"3".to_vec()),
+            ],
+            0,
+            &Limits::default(),
+        ));
+        unwrap!(data.append(&[(b"a".to_vec(), b"4".to_vec())], 3, &Limits::default()));
+
+        assert_eq!(data.get(b"a"), Some(&b"1".to_vec()));
+        assert_eq!(data.get_latest(b"a"), Some(&b"4".to_vec()));
+        assert_eq!(data.get_all(b"a"), vec![0, 2, 3]);
+        assert_eq!(data.get_latest(b"b"), Some(&b"2".to_vec()));
+        assert_eq!(data.get_latest(b"c"), None);
+        assert!(data.get_all(b"c").is_empty());
+
+        // A rejected append doesn't touch the index.
+        assert!(data
+            .append(&[(b"c".to_vec(), Vec::new())], 0, &Limits::default())
+            .is_err());
+        assert!(data.get_all(b"c").is_empty());
+
+        // The index is rebuilt on deserialisation.
+        let serialised = utils::serialise(&data);
+        let deserialised: PubSeqAppendOnlyData = unwrap!(bincode::deserialize(&serialised));
+        assert_eq!(deserialised.get_all(b"a"), vec![0, 2, 3]);
+        assert_eq!(deserialised.get_latest(b"a"), Some(&b"4".to_vec()));
+        assert!(deserialised == data);
+    }
+
     #[test]
     fn append_owners() {
         let owner_pk = PublicKey::Bls(SecretKey::random().public_key());
@@ -892,11 +1404,14 @@ mod tests {
         let mut data = SeqAppendOnlyData::<UnpubPermissions>::new(XorName([1; 32]), 10000);
 
         // Append the first owner with correct indices - should pass.
-        let res = data.append_owner(Owner {
-            public_key: owner_pk,
-            data_index: 0,
-            permissions_index: 0,
-        });
+        let res = data.append_owner(
+            Owner {
+                owners: owner_pk.into(),
+                data_index: 0,
+                permissions_index: 0,
+            },
+            &Limits::default(),
+        );
 
         match res {
             Ok(()) => (),
@@ -910,11 +1425,14 @@ mod tests {
         );
 
         // Append another owners entry with incorrect indices - should fail.
-        let res = data.append_owner(Owner {
-            public_key: owner_pk,
-            data_index: 64,
-            permissions_index: 0,
-        });
+        let res = data.append_owner(
+            Owner {
+                owners: owner_pk.into(),
+                data_index: 64,
+                permissions_index: 0,
+            },
+            &Limits::default(),
+        );
 
         match res {
             Err(_) => (),
@@ -928,10 +1446,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn effective_permissions() {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let user = PublicKey::Bls(SecretKey::random().public_key());
+        let stranger = PublicKey::Bls(SecretKey::random().public_key());
+
+        let mut inner = SeqAppendOnlyData::<PubPermissions>::new(XorName([1; 32]), 10000);
+        unwrap!(inner.append_owner(
+            Owner {
+                owners: owner.into(),
+                data_index: 0,
+                permissions_index: 0,
+            },
+            &Limits::default()
+        ));
+        let data = AData::PubSeq(inner.clone());
+
+        // Without permissions only the owner can act on the data.
+        let result = data.effective_permissions(stranger);
+        assert!(result.allowed.is_empty());
+        assert!(result
+            .denied
+            .values()
+            .all(|denial| *denial == Denial::NoPermissions));
+
+        // The user leaves appending to `User::Anyone`.
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(User::Anyone, PubPermissionSet::new(true, false));
+        let _ = permissions.insert(
+            User::Key(user),
+            PubPermissionSet {
+                append: None,
+                manage_permissions: Some(false),
+            },
+        );
+        unwrap!(inner.append_permissions(
+            PubPermissions {
+                permissions,
+                data_index: 0,
+                owner_entry_index: 1,
+            },
+            &Limits::default()
+        ));
+        let data = AData::PubSeq(inner);
+
+        let result = data.effective_permissions(owner);
+        assert_eq!(result.allowed.len(), 3);
+        assert!(result.denied.is_empty());
+
+        let result = data.effective_permissions(user);
+        assert!(result.allowed.contains(&Action::Read));
+        assert!(result.allowed.contains(&Action::Append));
+        assert_eq!(
+            result.denied.get(&Action::ManagePermissions),
+            Some(&Denial::NotAllowed)
+        );
+
+        // Keys without an entry can still read published data.
+        let result = data.effective_permissions(stranger);
+        assert!(result.allowed.contains(&Action::Read));
+        assert_eq!(result.denied.get(&Action::Append), Some(&Denial::NoEntry));
+
+        // The result agrees with the checks made on requests.
+        let append = Request::AppendSeq {
+            append: AppendOperation {
+                address: *data.address(),
+                values: Vec::new(),
+            },
+            index: 0,
+        };
+        for key in &[owner, user, stranger] {
+            let allowed = data.effective_permissions(*key).allowed;
+            assert_eq!(
+                data.check_permission(&append, *key).is_ok(),
+                allowed.contains(&Action::Append)
+            );
+        }
+    }
+
     #[test]
     fn seq_append_entries() {
         let mut data = SeqAppendOnlyData::<PubPermissions>::new(XorName([1; 32]), 10000);
-        let res = data.append(&[(b"hello".to_vec(), b"world".to_vec())], 0);
+        let res = data.append(
+            &[(b"hello".to_vec(), b"world".to_vec())],
+            0,
+            &Limits::default(),
+        );
 
         match res {
             Ok(()) => (),
@@ -939,6 +1540,80 @@ mod tests {
         }
     }
 
+    #[test]
+    fn verify() {
+        let owner_pk = PublicKey::Bls(SecretKey::random().public_key());
+        let name = XorName(rand::random());
+        let address = Address::new_unpub_seq(name, 10000);
+        let mut data = SeqAppendOnlyData::<UnpubPermissions>::new(name, 10000);
+        unwrap!(data.append_owner(
+            Owner {
+                owners: owner_pk.into(),
+                data_index: 0,
+                permissions_index: 0,
+            },
+            &Limits::default()
+        ));
+        unwrap!(data.append(&[(b"a".to_vec(), b"1".to_vec())], 0, &Limits::default()));
+        unwrap!(data.append_permissions(
+            UnpubPermissions {
+                permissions: BTreeMap::new(),
+                data_index: 1,
+                owner_entry_index: 1,
+            },
+            &Limits::default()
+        ));
+        unwrap!(data.append_owner(
+            Owner {
+                owners: owner_pk.into(),
+                data_index: 1,
+                permissions_index: 1,
+            },
+            &Limits::default()
+        ));
+        unwrap!(AData::UnpubSeq(data.clone()).verify(&address));
+
+        let invalid = |error| Err(Error::InvalidData(error));
+        assert_eq!(
+            AData::UnpubSeq(data.clone()).verify(&Address::new_pub_seq(name, 10000)),
+            invalid(VerificationError::KindMismatch)
+        );
+        assert_eq!(
+            AData::UnpubSeq(data.clone()).verify(&Address::new_unpub_seq(name, 1)),
+            invalid(VerificationError::TagMismatch)
+        );
+
+        // An owners entry appended before the permissions entry it refers to.
+        let mut tampered = data.clone();
+        tampered.inner.owners[0].permissions_index = 1;
+        assert_eq!(
+            AData::UnpubSeq(tampered).verify(&address),
+            invalid(VerificationError::InvalidOwnersEntry(0))
+        );
+
+        // A permissions entry referring to more entries than there are.
+        let mut tampered = data.clone();
+        tampered.inner.permissions[0].data_index = 2;
+        assert_eq!(
+            AData::UnpubSeq(tampered).verify(&address),
+            invalid(VerificationError::InvalidOwnersEntry(1))
+        );
+        let mut tampered = data.clone();
+        tampered.inner.data.clear();
+        assert_eq!(
+            AData::UnpubSeq(tampered).verify(&address),
+            invalid(VerificationError::InvalidOwnersEntry(1))
+        );
+        // A permissions entry referring to an owners entry appended after it.
+        let mut tampered = data;
+        let _ = tampered.inner.owners.pop();
+        tampered.inner.permissions[0].owner_entry_index = 2;
+        assert_eq!(
+            AData::UnpubSeq(tampered).verify(&address),
+            invalid(VerificationError::InvalidPermissionsEntry(0))
+        );
+    }
+
     #[test]
     fn assert_shell() {
         let owner_pk = PublicKey::Bls(SecretKey::random().public_key());
@@ -946,21 +1621,139 @@ mod tests {
 
         let mut data = SeqAppendOnlyData::<UnpubPermissions>::new(XorName([1; 32]), 10000);
 
-        let _ = data.append_owner(Owner {
-            public_key: owner_pk,
-            data_index: 0,
-            permissions_index: 0,
-        });
+        let _ = data.append_owner(
+            Owner {
+                owners: owner_pk.into(),
+                data_index: 0,
+                permissions_index: 0,
+            },
+            &Limits::default(),
+        );
 
-        let _ = data.append_owner(Owner {
-            public_key: owner_pk1,
-            data_index: 0,
-            permissions_index: 0,
-        });
+        let _ = data.append_owner(
+            Owner {
+                owners: owner_pk1.into(),
+                data_index: 0,
+                permissions_index: 0,
+            },
+            &Limits::default(),
+        );
 
         assert_eq!(data.owners_index(), unwrap!(data.shell(0)).owners_index());
     }
 
+    // Every index, both in and out of bounds, for lists of up to `MAX_LEN` items.
+    const MAX_LEN: u64 = 4;
+
+    fn all_indices() -> Vec<Index> {
+        (0..MAX_LEN + 3)
+            .flat_map(|idx| vec![Index::FromStart(idx), Index::FromEnd(idx)])
+            .collect()
+    }
+
+    // Reference model of `Index::resolve`, computed in signed arithmetic.
+    fn model_position(index: Index, len: u64) -> Option<u64> {
+        let pos = match index {
+            Index::FromStart(idx) => idx as i64,
+            Index::FromEnd(idx) => len as i64 - idx as i64,
+        };
+        if pos >= 0 && pos <= len as i64 {
+            Some(pos as u64)
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn resolve_index() {
+        for len in 0..=MAX_LEN {
+            for index in all_indices() {
+                match (index.resolve(len), model_position(index, len)) {
+                    (Ok(pos), Some(expected)) => assert_eq!(pos, expected),
+                    (Err(Error::InvalidIndexRange), None) => (),
+                    (res, expected) => panic!(
+                        "{:?} in a list of {}: got {:?}, expected {:?}",
+                        index, len, res, expected
+                    ),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn range_queries() {
+        let owner_pk = PublicKey::Bls(SecretKey::random().public_key());
+
+        for len in 0..=MAX_LEN {
+            let mut data = SeqAppendOnlyData::<PubPermissions>::new(XorName([1; 32]), 10000);
+            let entries: Entries = (0..len).map(|i| (vec![i as u8], vec![i as u8])).collect();
+            unwrap!(data.append(&entries, 0, &Limits::default()));
+            for _ in 0..len {
+                unwrap!(data.append_owner(
+                    Owner {
+                        owners: owner_pk.into(),
+                        data_index: len,
+                        permissions_index: 0,
+                    },
+                    &Limits::default()
+                ));
+            }
+
+            for start in all_indices() {
+                for end in all_indices() {
+                    let expected = match (model_position(start, len), model_position(end, len)) {
+                        (Some(start), Some(end)) if start <= end => {
+                            Some(start as usize..end as usize)
+                        }
+                        _ => None,
+                    };
+
+                    match (data.in_range(start, end), expected.clone()) {
+                        (Ok(range), Some(expected)) => assert_eq!(range, &entries[expected]),
+                        (Err(Error::InvalidIndexRange), None) => (),
+                        (res, expected) => panic!(
+                            "in_range({:?}, {:?}) of {}: got {:?}, expected {:?}",
+                            start, end, len, res, expected
+                        ),
+                    }
+
+                    match (data.owners_range(start, end), expected) {
+                        (Ok(range), Some(expected)) => assert_eq!(range.len(), expected.len()),
+                        (Err(Error::InvalidIndexRange), None) => (),
+                        (res, expected) => panic!(
+                            "owners_range({:?}, {:?}) of {}: got {:?}, expected {:?}",
+                            start,
+                            end,
+                            len,
+                            res.map(<[Owner]>::len),
+                            expected
+                        ),
+                    }
+
+                    // There are no permissions, so only empty ranges at position 0 are valid.
+                    let valid =
+                        model_position(start, 0) == Some(0) && model_position(end, 0) == Some(0);
+                    match data.permissions_range(start, end) {
+                        Ok(range) => assert!(valid && range.is_empty()),
+                        Err(Error::InvalidIndexRange) => assert!(!valid),
+                        Err(error) => panic!("Unexpected error: {:?}", error),
+                    }
+                }
+            }
+
+            for index in 0..MAX_LEN + 3 {
+                match data.shell(index) {
+                    Ok(shell) => {
+                        assert!(index <= len);
+                        assert_eq!(shell.entry_index(), 0);
+                    }
+                    Err(Error::InvalidIndexRange) => assert!(index > len),
+                    Err(error) => panic!("Unexpected error: {:?}", error),
+                }
+            }
+        }
+    }
+
     #[test]
     fn zbase32_encode_decode_adata_address() {
         let name = XorName(rand::random());
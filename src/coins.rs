@@ -24,7 +24,10 @@ const COIN_TO_RAW_CONVERSION: u64 = 1_000_000_000;
 pub const MAX_COINS_VALUE: Coins =
     Coins((u32::max_value() as u64 + 1) * COIN_TO_RAW_CONVERSION - 1);
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+/// The amount charged for each mutation which stores data on the network.
+pub const COST_OF_PUT: Coins = Coins(1);
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 /// Structure representing a safecoin amount.
 pub struct Coins(u64);
 
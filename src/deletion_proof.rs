@@ -0,0 +1,46 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{utils, OwnerSet, PublicKey, Result, Signature};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Approval of the owners to delete the data at `address`.
+///
+/// A deletion request carrying a proof is authorised by the proof rather than by its requester, so
+/// it can be relayed or batched by anyone. Vaults only accept each proof once, so that it can't
+/// delete data recreated at the same address.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct DeletionProof<A> {
+    /// Address of the data to delete.
+    pub address: A,
+    /// For `AppendOnlyData`, the owners index of the data when the proof was made, so that the
+    /// proof becomes invalid once the owners change. For `UnpubImmutableData`, which never
+    /// changes, any nonce chosen by the owner, which has to differ from those of earlier deletions
+    /// of the same data.
+    pub version: u64,
+    /// Signatures of the owners over `deletion_payload(&address, version)`.
+    pub signatures: BTreeMap<PublicKey, Signature>,
+}
+
+impl<A: Serialize> DeletionProof<A> {
+    /// Checks that the signatures satisfy the signing policy of `owners`.
+    pub fn verify(&self, owners: &OwnerSet) -> Result<()> {
+        owners.verify(
+            deletion_payload(&self.address, self.version),
+            &self.signatures,
+        )
+    }
+}
+
+/// Returns the bytes which the owners sign to approve deleting the data at `address` at
+/// `version`.
+pub fn deletion_payload<A: Serialize>(address: &A, version: u64) -> Vec<u8> {
+    utils::serialise(&(address, version))
+}
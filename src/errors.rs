@@ -7,6 +7,7 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+use crate::XorName;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
@@ -78,6 +79,14 @@ pub enum Error {
     InsufficientBalance,
     /// Expected data size exceeded.
     ExceededSize,
+    /// The given index or range of indices is out of bounds.
+    InvalidIndexRange,
+    /// A chunk doesn't match its description in a data map. Contains the name of the chunk.
+    InvalidChunk(XorName),
+    /// Data doesn't match the address it was expected at, or its structure is inconsistent.
+    InvalidData(VerificationError),
+    /// The deletion proof was already used to delete data at the same address.
+    DeletionProofUsed,
 }
 
 impl<T: Into<String>> From<T> for Error {
@@ -134,6 +143,10 @@ impl Display for Error {
             Error::DuplicateMessageId => write!(f, "MessageId already exists"),
             Error::UnexpectedDataReturned => write!(f, "Unexpected data variant"),
             Error::ExceededSize => write!(f, "Size of the structure exceeds the limit"),
+            Error::InvalidIndexRange => write!(f, "Index or range of indices is out of bounds"),
+            Error::InvalidChunk(_) => write!(f, "Chunk doesn't match its data map"),
+            Error::InvalidData(ref error) => write!(f, "Data failed verification: {:?}", error),
+            Error::DeletionProofUsed => write!(f, "Deletion proof has already been used"),
         }
     }
 }
@@ -169,6 +182,10 @@ impl error::Error for Error {
             Error::DuplicateMessageId => "MessageId already exists",
             Error::UnexpectedDataReturned => "Unexpected data variant",
             Error::ExceededSize => "Exceeded the size limit",
+            Error::InvalidIndexRange => "Invalid index range",
+            Error::InvalidChunk(_) => "Invalid chunk",
+            Error::InvalidData(_) => "Invalid data",
+            Error::DeletionProofUsed => "Deletion proof already used",
         }
     }
 }
@@ -182,4 +199,31 @@ pub enum EntryError {
     EntryExists(u8),
     /// Invalid version when updating an entry. Contains the current entry Key.
     InvalidSuccessor(u8),
+    /// The current value doesn't match the one expected by a conditional action. Contains the
+    /// hash of the current value.
+    ValueMismatch([u8; 32]),
+}
+
+/// Verification error for `Error::InvalidData`.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum VerificationError {
+    /// The data is of another kind than expected, e.g. published instead of unpublished.
+    KindMismatch,
+    /// The name of the data doesn't match the expected one.
+    NameMismatch,
+    /// The type tag of the data doesn't match the expected one.
+    TagMismatch,
+    /// The name of the data isn't derived from its content.
+    ContentMismatch,
+    /// The signing policy of the owners can't be satisfied.
+    InvalidOwners,
+    /// An owners entry is inconsistent with the entries or permissions history. Contains the
+    /// index of the owners entry.
+    InvalidOwnersEntry(u64),
+    /// A permissions entry is inconsistent with the entries or owners history. Contains the index
+    /// of the permissions entry.
+    InvalidPermissionsEntry(u64),
+    /// More previous values of an entry are retained than the history depth allows. Contains the
+    /// key of the entry.
+    InvalidHistory(Vec<u8>),
 }
@@ -11,7 +11,7 @@ pub mod app;
 pub mod client;
 pub mod node;
 
-use crate::{utils, Result, XorName};
+use crate::{utils, PublicKey, Result, XorName};
 use multibase::Decodable;
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Debug, Display, Formatter};
@@ -43,6 +43,17 @@ impl PublicId {
         }
     }
 
+    /// Returns the entity's public signing key.
+    ///
+    /// For nodes this is the ed25519 key, which is used to sign their requests.
+    pub fn public_key(&self) -> PublicKey {
+        match self {
+            PublicId::Node(pub_id) => PublicKey::from(*pub_id.ed25519_public_key()),
+            PublicId::Client(pub_id) => *pub_id.public_key(),
+            PublicId::App(pub_id) => *pub_id.public_key(),
+        }
+    }
+
     /// Returns the PublicId serialised and encoded in z-base-32.
     pub fn encode_to_zbase32(&self) -> String {
         utils::encode(&self)
@@ -7,20 +7,34 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use crate::{utils, Error, XorName, XOR_NAME_LEN};
+use crate::{
+    utils, DeletionProof, Error, OwnerSet, PublicKey, Verifiable, VerificationError, XorName,
+    XOR_NAME_LEN,
+};
 use bincode::serialized_size;
 use multibase::Decodable;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 use std::{
     fmt::{self, Debug, Formatter},
-    u64,
+    u64, usize,
 };
-use threshold_crypto::{PublicKey, PK_SIZE};
+use threshold_crypto::PK_SIZE;
 use tiny_keccak;
 
 /// Maximum allowed size for a serialised Immutable Data (ID) to grow to
 pub const MAX_IMMUTABLE_DATA_SIZE_IN_BYTES: u64 = 1024 * 1024 + 10 * 1024;
 
+// Version of the serialisation format of `UnpubImmutableData`. Version 1 introduced owners of any
+// `PublicKey` type, where version 0 only allowed BLS keys.
+const UNPUB_IDATA_FORMAT_VERSION: u8 = 1;
+
+// Versioned `UnpubImmutableData` is serialised starting with this marker, followed by the format
+// version. Version 0 starts with the length of the value instead, which never reaches the marker.
+const UNPUB_IDATA_FORMAT_MARKER: u64 = u64::MAX;
+
 #[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone)]
 pub struct UnpubImmutableData {
     /// Address.
@@ -34,14 +48,12 @@ pub struct UnpubImmutableData {
 
 impl UnpubImmutableData {
     /// Creates a new instance of `UnpubImmutableData`
+    ///
+    /// The name is derived from the value and the owners. Data owned by a BLS key gets the same
+    /// name as it did when only BLS keys could own unpublished data.
     pub fn new(value: Vec<u8>, owners: PublicKey) -> Self {
-        // TODO: Use low-level arrays or slices instead of Vec.
-        let mut bytes = Vec::with_capacity(XOR_NAME_LEN + PK_SIZE);
-        bytes.extend_from_slice(&tiny_keccak::sha3_256(&value));
-        bytes.extend_from_slice(&owners.to_bytes());
-        let address = Address::Unpub(XorName(tiny_keccak::sha3_256(&bytes)));
         Self {
-            address,
+            address: Address::Unpub(unpub_name(&value, &owners)),
             value,
             owners,
         }
@@ -81,21 +93,101 @@ impl UnpubImmutableData {
     pub fn validate_size(&self) -> bool {
         self.serialised_size() <= MAX_IMMUTABLE_DATA_SIZE_IN_BYTES
     }
+
+    /// Checks that `proof` approves deleting this data and is signed by its owner.
+    pub fn check_deletion(&self, proof: &DeletionProof<Address>) -> Result<(), Error> {
+        if proof.address != self.address {
+            return Err(Error::AccessDenied);
+        }
+        proof.verify(&OwnerSet::from(self.owners))
+    }
+}
+
+// Derives the name of unpublished data from its value and owners.
+//
+// BLS keys are hashed as their raw bytes, as they were before other key types were allowed. Other
+// keys are hashed in their serialised form, whose length differs from that of a raw BLS key, so
+// names of differently owned data can't collide.
+fn unpub_name(value: &[u8], owners: &PublicKey) -> XorName {
+    // TODO: Use low-level arrays or slices instead of Vec.
+    let mut bytes = Vec::with_capacity(XOR_NAME_LEN + PK_SIZE);
+    bytes.extend_from_slice(&tiny_keccak::sha3_256(value));
+    match owners {
+        PublicKey::Bls(key) => bytes.extend_from_slice(&key.to_bytes()),
+        PublicKey::Ed25519(_) | PublicKey::BlsShare(_) => {
+            bytes.extend_from_slice(&utils::serialise(owners))
+        }
+    }
+    XorName(tiny_keccak::sha3_256(&bytes))
 }
 
 impl Serialize for UnpubImmutableData {
     fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
-        (&self.value, &self.owners).serialize(serialiser)
+        (
+            UNPUB_IDATA_FORMAT_MARKER,
+            UNPUB_IDATA_FORMAT_VERSION,
+            &self.value,
+            &self.owners,
+        )
+            .serialize(serialiser)
     }
 }
 
 impl<'de> Deserialize<'de> for UnpubImmutableData {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let (value, owners): (Vec<u8>, PublicKey) = Deserialize::deserialize(deserializer)?;
-        Ok(UnpubImmutableData::new(value, owners))
+        // The length is only an upper bound, as the visitor decides how many elements to read
+        // from the first one.
+        deserializer.deserialize_tuple(usize::MAX, UnpubImmutableDataVisitor)
+    }
+}
+
+struct UnpubImmutableDataVisitor;
+
+impl<'de> Visitor<'de> for UnpubImmutableDataVisitor {
+    type Value = UnpubImmutableData;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "a serialised UnpubImmutableData")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let first: u64 = next_element(&mut seq)?;
+        if first == UNPUB_IDATA_FORMAT_MARKER {
+            let version: u8 = next_element(&mut seq)?;
+            if version != UNPUB_IDATA_FORMAT_VERSION {
+                return Err(de::Error::custom(format!(
+                    "Unsupported UnpubImmutableData format version {}",
+                    version
+                )));
+            }
+            let value = next_element(&mut seq)?;
+            let owners = next_element(&mut seq)?;
+            return Ok(UnpubImmutableData::new(value, owners));
+        }
+
+        // Version 0: `(Vec<u8>, threshold_crypto::PublicKey)`, so `first` is the length of the
+        // value, whose bytes follow.
+        if first > MAX_IMMUTABLE_DATA_SIZE_IN_BYTES {
+            return Err(de::Error::invalid_length(first as usize, &self));
+        }
+        let mut value = Vec::with_capacity(first as usize);
+        for _ in 0..first {
+            value.push(next_element(&mut seq)?);
+        }
+        let owners: threshold_crypto::PublicKey = next_element(&mut seq)?;
+        Ok(UnpubImmutableData::new(value, PublicKey::Bls(owners)))
     }
 }
 
+fn next_element<'de, A, T>(seq: &mut A) -> Result<T, A::Error>
+where
+    A: SeqAccess<'de>,
+    T: Deserialize<'de>,
+{
+    seq.next_element()?
+        .ok_or_else(|| de::Error::custom("Truncated UnpubImmutableData"))
+}
+
 impl Debug for UnpubImmutableData {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         // TODO: Output owners?
@@ -211,6 +303,13 @@ pub enum Kind {
 }
 
 impl Kind {
+    pub fn address(&self) -> &Address {
+        match self {
+            Kind::Unpub(ref data) => data.address(),
+            Kind::Pub(ref data) => data.address(),
+        }
+    }
+
     pub fn name(&self) -> &XorName {
         match self {
             Kind::Unpub(ref data) => data.name(),
@@ -224,6 +323,43 @@ impl Kind {
             Kind::Pub(_) => true,
         }
     }
+
+    /// Return true if the size is valid
+    pub fn validate_size(&self) -> bool {
+        match self {
+            Kind::Unpub(ref data) => data.validate_size(),
+            Kind::Pub(ref data) => data.validate_size(),
+        }
+    }
+}
+
+impl Verifiable for Kind {
+    type Address = Address;
+
+    /// Checks that the data is of the expected kind and that its name is derived from its
+    /// content, and owners if unpublished.
+    fn verify(&self, address: &Address) -> Result<(), Error> {
+        let (actual, name) = match self {
+            Kind::Unpub(ref data) => (
+                data.address(),
+                Address::Unpub(unpub_name(data.value(), data.owners())),
+            ),
+            Kind::Pub(ref data) => (
+                data.address(),
+                Address::Pub(XorName(tiny_keccak::sha3_256(data.value()))),
+            ),
+        };
+        if actual.published() != address.published() {
+            return Err(Error::InvalidData(VerificationError::KindMismatch));
+        }
+        if actual.name() != address.name() {
+            return Err(Error::InvalidData(VerificationError::NameMismatch));
+        }
+        if name != *actual {
+            return Err(Error::InvalidData(VerificationError::ContentMismatch));
+        }
+        Ok(())
+    }
 }
 
 impl From<UnpubImmutableData> for Kind {
@@ -240,12 +376,12 @@ impl From<ImmutableData> for Kind {
 
 #[cfg(test)]
 mod tests {
-    use super::{utils, Address, ImmutableData, UnpubImmutableData, XorName};
+    use super::{utils, Address, ImmutableData, Kind, UnpubImmutableData, XorName};
+    use crate::{ClientFullId, Error, PublicKey, Verifiable, VerificationError};
     use bincode::deserialize as deserialise;
     use hex::encode;
-    use rand::{self, Rng, SeedableRng};
-    use rand_xorshift::XorShiftRng;
-    use std::{env, iter, thread};
+    use rand::Rng;
+    use std::iter;
     use threshold_crypto::SecretKey;
     use unwrap::unwrap;
 
@@ -254,8 +390,10 @@ mod tests {
         let data1 = b"Hello".to_vec();
         let data2 = b"Goodbye".to_vec();
 
-        let owner1 = SecretKey::random().public_key();
-        let owner2 = SecretKey::random().public_key();
+        let owner1 = PublicKey::Bls(SecretKey::random().public_key());
+        let owner2 = *ClientFullId::new_ed25519(&mut rand::thread_rng())
+            .public_id()
+            .public_key();
 
         let idata1 = UnpubImmutableData::new(data1.clone(), owner1);
         let idata2 = UnpubImmutableData::new(data1, owner2);
@@ -269,6 +407,73 @@ mod tests {
         assert_ne!(idata2.name(), idata3.name());
     }
 
+    #[test]
+    fn bls_owned_name() {
+        // Name of BLS-owned data as derived when only BLS keys could own unpublished data.
+        let secret_key = SecretKey::random();
+        let mut bytes = tiny_keccak::sha3_256(b"Hello").to_vec();
+        bytes.extend_from_slice(&secret_key.public_key().to_bytes());
+        let expected = XorName(tiny_keccak::sha3_256(&bytes));
+
+        let data = UnpubImmutableData::new(b"Hello".to_vec(), secret_key.public_key().into());
+        assert_eq!(*data.name(), expected);
+        let parsed: UnpubImmutableData = unwrap!(deserialise(&utils::serialise(&data)));
+        assert_eq!(*parsed.name(), expected);
+
+        // Data serialised in another format version is rejected.
+        let mut serialised = utils::serialise(&data);
+        serialised[8] += 1;
+        assert!(deserialise::<UnpubImmutableData>(&serialised).is_err());
+    }
+
+    #[test]
+    fn legacy_format() {
+        // Serialised as it was before the format was versioned.
+        let secret_key = SecretKey::random();
+        let serialised = utils::serialise(&(b"Hello".to_vec(), secret_key.public_key()));
+
+        let parsed: UnpubImmutableData = unwrap!(deserialise(&serialised));
+        let data = UnpubImmutableData::new(b"Hello".to_vec(), secret_key.public_key().into());
+        assert_eq!(parsed, data);
+        assert_eq!(parsed.value(), &b"Hello".to_vec());
+
+        // It is reserialised in the current format.
+        assert_ne!(utils::serialise(&parsed), serialised);
+
+        // Truncated data is rejected.
+        assert!(deserialise::<UnpubImmutableData>(&serialised[..serialised.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn verify() {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let unpub = UnpubImmutableData::new(b"Hello".to_vec(), owner);
+        let address = *unpub.address();
+        unwrap!(Kind::from(unpub.clone()).verify(&address));
+
+        let invalid = |error| Err(Error::InvalidData(error));
+        assert_eq!(
+            Kind::from(unpub.clone()).verify(&Address::Pub(*address.name())),
+            invalid(VerificationError::KindMismatch)
+        );
+        assert_eq!(
+            Kind::from(ImmutableData::new(b"Hello".to_vec())).verify(&address),
+            invalid(VerificationError::KindMismatch)
+        );
+        assert_eq!(
+            Kind::from(unpub.clone()).verify(&Address::Unpub(XorName(rand::random()))),
+            invalid(VerificationError::NameMismatch)
+        );
+
+        // Content which doesn't hash to the name.
+        let mut tampered = unpub;
+        tampered.owners = PublicKey::Bls(SecretKey::random().public_key());
+        assert_eq!(
+            Kind::from(tampered).verify(&address),
+            invalid(VerificationError::ContentMismatch)
+        );
+    }
+
     #[test]
     fn deterministic_test() {
         let value = "immutable data value".to_owned().into_bytes();
@@ -281,7 +486,7 @@ mod tests {
 
     #[test]
     fn serialisation() {
-        let mut rng = get_rng();
+        let mut rng = utils::get_rng();
         let len = rng.gen_range(1, 10_000);
         let value = iter::repeat_with(|| rng.gen()).take(len).collect();
         let immutable_data = ImmutableData::new(value);
@@ -290,27 +495,6 @@ mod tests {
         assert_eq!(immutable_data, parsed);
     }
 
-    fn get_rng() -> XorShiftRng {
-        let env_var_name = "RANDOM_SEED";
-        let seed = env::var(env_var_name)
-            .ok()
-            .map(|value| {
-                unwrap!(
-                    value.parse::<u64>(),
-                    "Env var 'RANDOM_SEED={}' is not a valid u64.",
-                    value
-                )
-            })
-            .unwrap_or_else(rand::random);
-        println!(
-            "To replay this '{}', set env var {}={}",
-            unwrap!(thread::current().name()),
-            env_var_name,
-            seed
-        );
-        XorShiftRng::seed_from_u64(seed)
-    }
-
     #[test]
     fn zbase32_encode_decode_idata_address() {
         let name = XorName(rand::random());
@@ -57,26 +57,36 @@
 
 mod append_only_data;
 mod coins;
+mod deletion_proof;
 mod errors;
 mod identity;
 mod immutable_data;
+mod limits;
+#[cfg(feature = "mock")]
+pub mod mock;
 mod mutable_data;
+mod owner_set;
 mod public_key;
 mod request;
 mod response;
+mod self_encryption;
+mod signature_accumulator;
 mod utils;
+mod verifiable;
 
 pub use append_only_data::{
     AData, Action as ADataAction, Address as ADataAddress, AppendOnlyData,
-    AppendOperation as ADataAppend, Entries, Index as ADataIndex, Indices as ADataIndices,
-    Owner as ADataOwner, PubPermissionSet as ADataPubPermissionSet,
-    PubPermissions as ADataPubPermissions, PubSeqAppendOnlyData, PubUnseqAppendOnlyData,
-    SeqAppendOnly, UnpubPermissionSet as ADataUnpubPermissionSet,
-    UnpubPermissions as ADataUnpubPermissions, UnpubSeqAppendOnlyData, UnpubUnseqAppendOnlyData,
-    UnseqAppendOnly, User as ADataUser,
+    AppendOperation as ADataAppend, Denial as ADataDenial,
+    EffectivePermissions as ADataEffectivePermissions, Entries, Event as ADataEvent,
+    Index as ADataIndex, Indices as ADataIndices, Owner as ADataOwner,
+    PubPermissionSet as ADataPubPermissionSet, PubPermissions as ADataPubPermissions,
+    PubSeqAppendOnlyData, PubUnseqAppendOnlyData, SeqAppendOnly,
+    UnpubPermissionSet as ADataUnpubPermissionSet, UnpubPermissions as ADataUnpubPermissions,
+    UnpubSeqAppendOnlyData, UnpubUnseqAppendOnlyData, UnseqAppendOnly, User as ADataUser,
 };
-pub use coins::{Coins, MAX_COINS_VALUE};
-pub use errors::{EntryError, Error, Result};
+pub use coins::{Coins, COST_OF_PUT, MAX_COINS_VALUE};
+pub use deletion_proof::{deletion_payload, DeletionProof};
+pub use errors::{EntryError, Error, Result, VerificationError};
 pub use identity::{
     app::{FullId as AppFullId, PublicId as AppPublicId},
     client::{FullId as ClientFullId, PublicId as ClientPublicId},
@@ -87,18 +97,35 @@ pub use immutable_data::{
     Address as IDataAddress, ImmutableData, Kind as IDataKind, UnpubImmutableData,
     MAX_IMMUTABLE_DATA_SIZE_IN_BYTES,
 };
+pub use limits::{
+    Limits, MAX_APPEND_ONLY_DATA_OWNERS, MAX_APPEND_ONLY_DATA_PERMISSIONS, MAX_DATA_SIZE_IN_BYTES,
+    MAX_ENTRY_KEY_SIZE_IN_BYTES, MAX_ENTRY_VALUE_SIZE_IN_BYTES, MAX_MUTABLE_DATA_ENTRIES,
+};
 pub use mutable_data::{
-    Action as MDataAction, Address as MDataAddress, MutableData,
-    PermissionSet as MDataPermissionSet, SeqEntryAction as MDataSeqEntryAction,
-    SeqEntryActions as MDataSeqEntryActions, SeqMutableData,
+    owner_change_payload as mdata_owner_change_payload, Action as MDataAction,
+    Address as MDataAddress, Change as MDataChange, Denial as MDataDenial,
+    EffectivePermissions as MDataEffectivePermissions, Event as MDataEvent, MutableData,
+    Page as MDataPage, PageQuery as MDataPageQuery, PermissionSet as MDataPermissionSet,
+    PermissionsDiff as MDataPermissionsDiff, SeqEntryAction as MDataSeqEntryAction,
+    SeqEntryActions as MDataSeqEntryActions, SeqMutableData, Timestamp as MDataTimestamp,
     UnseqEntryAction as MDataUnseqEntryAction, UnseqEntryActions as MDataUnseqEntryActions,
-    UnseqMutableData, Value as MDataValue,
+    UnseqMutableData, UnseqReplica as MDataUnseqReplica, User as MDataUser, Value as MDataValue,
+    ValueHash as MDataValueHash, MAX_MUTABLE_DATA_PAGE_SIZE,
 };
+pub use owner_set::OwnerSet;
 pub use public_key::{PublicKey, Signature};
-pub use request::{AccountData, Request, MAX_ACCOUNT_DATA_BYTES};
-pub use response::{Response, Transaction};
+pub use request::{
+    AccountData, DataType, Request, RequestType, SignedRequest, MAX_ACCOUNT_DATA_BYTES,
+};
+pub use response::{Notification, Response, ResponseKind, Transaction};
+pub use self_encryption::{
+    ChunkInfo, ChunkReader, ChunkStore, ChunkWriter, DataMap, SelfEncryptor, MAX_CHUNK_SIZE,
+    MIN_CHUNK_SIZE,
+};
 pub use sha3::Sha3_512 as Ed25519Digest;
-pub use utils::verify_signature;
+pub use signature_accumulator::SignatureAccumulator;
+pub use utils::{request_payload, verify_signature};
+pub use verifiable::Verifiable;
 
 use hex_fmt::HexFmt;
 use multibase::Decodable;
@@ -175,6 +202,11 @@ pub enum Message {
         response: Response,
         message_id: MessageId,
     },
+    /// Unsolicited update pushed to a subscriber.
+    Notification {
+        notification: Notification,
+        message_id: MessageId,
+    },
 }
 
 impl Message {
@@ -182,6 +214,7 @@ impl Message {
         match self {
             Message::Request { message_id, .. } => *message_id,
             Message::Response { message_id, .. } => *message_id,
+            Message::Notification { message_id, .. } => *message_id,
         }
     }
 }
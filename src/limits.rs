@@ -0,0 +1,61 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of entries in a Mutable Data.
+pub const MAX_MUTABLE_DATA_ENTRIES: u64 = 1000;
+
+/// Maximum number of entries in the permissions history of an Append Only Data.
+pub const MAX_APPEND_ONLY_DATA_PERMISSIONS: u64 = 100;
+
+/// Maximum number of entries in the owners history of an Append Only Data.
+pub const MAX_APPEND_ONLY_DATA_OWNERS: u64 = 100;
+
+/// Maximum size of an entry key in a Mutable Data or an Append Only Data.
+pub const MAX_ENTRY_KEY_SIZE_IN_BYTES: u64 = 1024;
+
+/// Maximum size of an entry value in a Mutable Data or an Append Only Data.
+pub const MAX_ENTRY_VALUE_SIZE_IN_BYTES: u64 = 100 * 1024;
+
+/// Maximum allowed size for a serialised Mutable Data or Append Only Data to grow to.
+pub const MAX_DATA_SIZE_IN_BYTES: u64 = 1024 * 1024;
+
+/// Limits on the number and size of entries in a Mutable Data or an Append Only Data.
+///
+/// `Limits::default()` returns the network-wide limits. Vaults pass the limits they enforce to
+/// every mutation which can grow the data.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Limits {
+    /// Maximum number of entries of a Mutable Data.
+    pub max_entries: u64,
+    /// Maximum size of an entry key.
+    pub max_key_size: u64,
+    /// Maximum size of an entry value.
+    pub max_value_size: u64,
+    /// Maximum size of the whole serialised data.
+    pub max_size: u64,
+    /// Maximum number of entries in the permissions history of an Append Only Data.
+    pub max_permissions: u64,
+    /// Maximum number of entries in the owners history of an Append Only Data.
+    pub max_owners: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_entries: MAX_MUTABLE_DATA_ENTRIES,
+            max_key_size: MAX_ENTRY_KEY_SIZE_IN_BYTES,
+            max_value_size: MAX_ENTRY_VALUE_SIZE_IN_BYTES,
+            max_size: MAX_DATA_SIZE_IN_BYTES,
+            max_permissions: MAX_APPEND_ONLY_DATA_PERMISSIONS,
+            max_owners: MAX_APPEND_ONLY_DATA_OWNERS,
+        }
+    }
+}
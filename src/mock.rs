@@ -0,0 +1,2200 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! In-memory reference implementation of the vault side of `Request`/`Response`.
+//!
+//! The `Vault` in this module stores all the data types locally and applies every `Request`
+//! variant to them, returning the exact `Response` variant a real vault is expected to return. It
+//! is meant to be used as the canonical executable spec of the data types by client and vault test
+//! harnesses.
+
+use crate::{
+    deletion_payload, request::TransactionId, utils, AData, ADataAddress, ADataEvent, ADataIndex,
+    AccountData, AppPermissions, AppendOnlyData, ChunkStore, Coins, DeletionProof, Error,
+    IDataAddress, IDataKind, ImmutableData, Limits, MDataAddress, MDataChange, MDataEvent, Message,
+    MessageId, MutableData, Notification, OwnerSet, PublicId, PublicKey, Request, Response, Result,
+    SeqAppendOnly, SeqMutableData, Transaction, UnseqAppendOnly, UnseqMutableData, XorName,
+};
+use serde::Serialize;
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet};
+
+/// Local in-memory store which executes requests the way the vaults do.
+#[derive(Default)]
+pub struct Vault {
+    idata: BTreeMap<IDataAddress, IDataKind>,
+    mdata: BTreeMap<MDataAddress, MData>,
+    adata: BTreeMap<ADataAddress, AData>,
+    balances: BTreeMap<XorName, Balance>,
+    accounts: BTreeMap<XorName, AccountData>,
+    auth_keys: BTreeMap<XorName, AuthKeys>,
+    // Subscribers of each MData.
+    mdata_subscriptions: BTreeMap<MDataAddress, BTreeSet<PublicKey>>,
+    // Subscribers of each AData, with the index of the next entry they are to be notified of.
+    adata_subscriptions: BTreeMap<ADataAddress, BTreeMap<PublicKey, u64>>,
+    // Notifications not yet collected by their recipients.
+    notifications: BTreeMap<PublicKey, Vec<Message>>,
+    // Limits enforced on every MData mutation.
+    mdata_limits: Limits,
+    // Limits enforced on every AData mutation.
+    adata_limits: Limits,
+    // Payloads of the deletion proofs already used, so that they can't be replayed.
+    used_deletion_proofs: BTreeSet<Vec<u8>>,
+}
+
+#[derive(Clone)]
+enum MData {
+    Seq(SeqMutableData),
+    Unseq(UnseqMutableData),
+}
+
+impl MData {
+    fn as_mutable_data(&self) -> &dyn MutableData {
+        match self {
+            MData::Seq(data) => data,
+            MData::Unseq(data) => data,
+        }
+    }
+
+    fn as_mutable_data_mut(&mut self) -> &mut dyn MutableData {
+        match self {
+            MData::Seq(data) => data,
+            MData::Unseq(data) => data,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Balance {
+    coins: Coins,
+    // Credits received by this balance, keyed by their transaction ID.
+    transactions: BTreeMap<TransactionId, Coins>,
+}
+
+#[derive(Default)]
+struct AuthKeys {
+    keys: BTreeMap<PublicKey, AppPermissions>,
+    version: u64,
+}
+
+impl Vault {
+    /// Creates an empty vault.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the limits enforced on MutableData, instead of the network-wide ones.
+    pub fn set_mdata_limits(&mut self, limits: Limits) {
+        self.mdata_limits = limits;
+    }
+
+    /// Sets the limits enforced on AppendOnlyData, instead of the network-wide ones.
+    pub fn set_adata_limits(&mut self, limits: Limits) {
+        self.adata_limits = limits;
+    }
+
+    /// Credits `amount` to the coin balance of `owner`, creating the balance if it doesn't exist.
+    ///
+    /// There is no request which mints coins, so this is the way to seed balances for testing.
+    pub fn credit(&mut self, owner: PublicKey, amount: Coins) -> Result<()> {
+        let balance = self.balances.entry(XorName::from(owner)).or_default();
+        balance.coins = balance
+            .coins
+            .checked_add(amount)
+            .ok_or(Error::ExcessiveValue)?;
+        Ok(())
+    }
+
+    /// Handles a `Message::Request` sent by `requester` and returns the matching
+    /// `Message::Response`.
+    ///
+    /// If the message is signed, the signature is verified against the requester's key. Unsigned
+    /// messages are only accepted for reads of published data. The signatures of the co-signers of
+    /// a `Request::CoSigned` are verified too. Returns `Error::InvalidOperation` if `message` is
+    /// not a request.
+    pub fn handle_message(&mut self, requester: &PublicId, message: Message) -> Result<Message> {
+        match message {
+            Message::Request {
+                request,
+                message_id,
+                signature,
+            } => {
+                let verified = match signature {
+                    Some(signature) => utils::verify_signature(
+                        &signature,
+                        &requester.public_key(),
+                        &request,
+                        &message_id,
+                    ),
+                    None if request.requires_signature() => Err(Error::InvalidSignature),
+                    None => Ok(()),
+                }
+                .and_then(|()| verify_co_signatures(&request, &message_id));
+                let response = match verified {
+                    Ok(()) => self.handle_request(requester, request),
+                    Err(error) => error_response(&request, error),
+                };
+                Ok(Message::Response {
+                    response,
+                    message_id,
+                })
+            }
+            Message::Response { .. } | Message::Notification { .. } => Err(Error::InvalidOperation),
+        }
+    }
+
+    /// Removes and returns the notifications pushed to `subscriber` so far, oldest first.
+    pub fn take_notifications(&mut self, subscriber: &PublicId) -> Vec<Message> {
+        self.notifications
+            .remove(&subscriber.public_key())
+            .unwrap_or_default()
+    }
+
+    /// Applies `request` on behalf of `requester` and returns the response.
+    ///
+    /// No signature checks are performed: the caller is trusted to have authenticated the
+    /// requester.
+    pub fn handle_request(&mut self, requester: &PublicId, request: Request) -> Response {
+        let response = match self.authorise(requester, &request) {
+            Ok(()) => self.apply(requester, &request),
+            Err(error) => error_response(&request, error),
+        };
+        debug_assert!(
+            response.is_valid_for(&request),
+            "{:?} is not a valid response for {:?}",
+            response,
+            request
+        );
+        response
+    }
+
+    // Co-signed requests are applied as the request they carry, but the whole `request` is passed
+    // on to the permission checks so that the co-signers count towards the owners.
+    fn apply(&mut self, requester: &PublicId, request: &Request) -> Response {
+        let key = requester.public_key();
+        let mdata_limits = self.mdata_limits;
+        let adata_limits = self.adata_limits;
+
+        match request.inner() {
+            //
+            // ===== Immutable Data =====
+            //
+            Request::PutIData(data) => Response::Mutation(self.put_idata(key, data.clone())),
+            Request::GetIData(address) => Response::GetIData(self.get_idata(key, address)),
+            Request::DeleteUnpubIData(proof) => Response::Mutation(self.delete_unpub_idata(proof)),
+            //
+            // ===== Mutable Data =====
+            //
+            Request::PutUnseqMData(data) => Response::Mutation(self.put_mdata(
+                key,
+                *data.address(),
+                data.owners(),
+                MData::Unseq(data.clone()),
+            )),
+            Request::PutSeqMData(data) => Response::Mutation(self.put_mdata(
+                key,
+                *data.address(),
+                data.owners(),
+                MData::Seq(data.clone()),
+            )),
+            Request::GetMData(address) => match self.mdata(key, address, request) {
+                Ok(MData::Seq(data)) => Response::GetSeqMData(Ok(data.clone())),
+                Ok(MData::Unseq(data)) => Response::GetUnseqMData(Ok(data.clone())),
+                Err(error) => error_response(request, error),
+            },
+            Request::GetMDataValue {
+                address,
+                key: entry,
+            } => match self.mdata(key, address, request) {
+                Ok(MData::Seq(data)) => {
+                    Response::GetSeqMDataValue(data.get(entry).cloned().ok_or(Error::NoSuchEntry))
+                }
+                Ok(MData::Unseq(data)) => {
+                    Response::GetUnseqMDataValue(data.get(entry).cloned().ok_or(Error::NoSuchEntry))
+                }
+                Err(error) => error_response(request, error),
+            },
+            Request::GetMDataValueAtVersion {
+                address,
+                key: entry,
+                version,
+            } => Response::GetMDataValueAtVersion(self.mdata(key, address, request).and_then(
+                |data| {
+                    match data {
+                        MData::Seq(data) => data
+                            .get_at_version(entry, *version)
+                            .cloned()
+                            .ok_or(Error::NoSuchEntry),
+                        MData::Unseq(_) => Err(Error::InvalidOperation),
+                    }
+                },
+            )),
+            Request::ListMDataValueHistory {
+                address,
+                key: entry,
+            } => Response::ListMDataValueHistory(self.mdata(key, address, request).and_then(
+                |data| match data {
+                    MData::Seq(data) => {
+                        let history = data.value_history(entry);
+                        if history.is_empty() {
+                            Err(Error::NoSuchEntry)
+                        } else {
+                            Ok(history)
+                        }
+                    }
+                    MData::Unseq(_) => Err(Error::InvalidOperation),
+                },
+            )),
+            Request::DeleteMData(address) => {
+                Response::Mutation(self.delete_mdata(key, address, request))
+            }
+            Request::GetMDataShell(address) => match self.mdata(key, address, request) {
+                Ok(MData::Seq(data)) => Response::GetSeqMDataShell(Ok(data.shell())),
+                Ok(MData::Unseq(data)) => Response::GetUnseqMDataShell(Ok(data.shell())),
+                Err(error) => error_response(request, error),
+            },
+            Request::GetMDataVersion(address) => Response::GetMDataVersion(
+                self.mdata(key, address, request)
+                    .map(|data| data.as_mutable_data().version()),
+            ),
+            Request::ListMDataEntries(address) => match self.mdata(key, address, request) {
+                Ok(MData::Seq(data)) => Response::ListSeqMDataEntries(Ok(data.entries().clone())),
+                Ok(MData::Unseq(data)) => {
+                    Response::ListUnseqMDataEntries(Ok(data.entries().clone()))
+                }
+                Err(error) => error_response(request, error),
+            },
+            Request::ListMDataKeys(address) => Response::ListMDataKeys(
+                self.mdata(key, address, request)
+                    .map(|data| data.as_mutable_data().keys()),
+            ),
+            Request::ListMDataValues(address) => match self.mdata(key, address, request) {
+                Ok(MData::Seq(data)) => Response::ListSeqMDataValues(Ok(data.values())),
+                Ok(MData::Unseq(data)) => Response::ListUnseqMDataValues(Ok(data.values())),
+                Err(error) => error_response(request, error),
+            },
+            Request::ListMDataEntriesPage { address, query } => {
+                match self.mdata(key, address, request) {
+                    Ok(MData::Seq(data)) => {
+                        Response::ListSeqMDataEntriesPage(data.entries_page(query))
+                    }
+                    Ok(MData::Unseq(data)) => {
+                        Response::ListUnseqMDataEntriesPage(data.entries_page(query))
+                    }
+                    Err(error) => error_response(request, error),
+                }
+            }
+            Request::ListMDataKeysPage { address, query } => Response::ListMDataKeysPage(
+                self.mdata(key, address, request)
+                    .and_then(|data| data.as_mutable_data().keys_page(query)),
+            ),
+            Request::ListMDataValuesPage { address, query } => {
+                match self.mdata(key, address, request) {
+                    Ok(MData::Seq(data)) => {
+                        Response::ListSeqMDataValuesPage(data.values_page(query))
+                    }
+                    Ok(MData::Unseq(data)) => {
+                        Response::ListUnseqMDataValuesPage(data.values_page(query))
+                    }
+                    Err(error) => error_response(request, error),
+                }
+            }
+            Request::SetMDataUserPermissions {
+                address,
+                user,
+                permissions,
+                version,
+            } => {
+                let result = self.mdata_mut(key, address, request).and_then(|data| {
+                    data.as_mutable_data_mut().set_user_permissions(
+                        *user,
+                        permissions.clone(),
+                        *version,
+                        &mdata_limits,
+                    )
+                });
+                if result.is_ok() {
+                    self.notify_mdata_subscribers(
+                        address,
+                        MDataChange::SetUserPermissions {
+                            user: *user,
+                            permissions: permissions.clone(),
+                        },
+                    );
+                }
+                Response::Mutation(result)
+            }
+            Request::DelMDataUserPermissions {
+                address,
+                user,
+                version,
+            } => {
+                let result = self.mdata_mut(key, address, request).and_then(|data| {
+                    data.as_mutable_data_mut()
+                        .del_user_permissions(*user, *version)
+                });
+                if result.is_ok() {
+                    self.notify_mdata_subscribers(
+                        address,
+                        MDataChange::DelUserPermissions { user: *user },
+                    );
+                }
+                Response::Mutation(result)
+            }
+            Request::ListMDataPermissions(address) => Response::ListMDataPermissions(
+                self.mdata(key, address, request)
+                    .map(|data| data.as_mutable_data().permissions()),
+            ),
+            Request::ListMDataUserPermissions { address, user } => {
+                Response::ListMDataUserPermissions(self.mdata(key, address, request).and_then(
+                    |data| {
+                        let permissions = data.as_mutable_data().user_permissions(*user)?;
+                        Ok(permissions.clone())
+                    },
+                ))
+            }
+            Request::MutateSeqMDataEntries { address, actions } => {
+                let result = self
+                    .mdata_mut(key, address, request)
+                    .and_then(|data| match data {
+                        MData::Seq(data) => data.mutate_entries_cosigned(
+                            actions.clone(),
+                            key,
+                            &request.co_signers(),
+                            &mdata_limits,
+                        ),
+                        MData::Unseq(_) => Err(Error::InvalidOperation),
+                    });
+                if result.is_ok() {
+                    self.notify_mdata_subscribers(
+                        address,
+                        MDataChange::SeqEntries(actions.clone()),
+                    );
+                }
+                Response::Mutation(result)
+            }
+            Request::MutateUnseqMDataEntries { address, actions } => {
+                let result = self
+                    .mdata_mut(key, address, request)
+                    .and_then(|data| match data {
+                        MData::Unseq(data) => data.mutate_entries_cosigned(
+                            actions.clone(),
+                            key,
+                            &request.co_signers(),
+                            &mdata_limits,
+                        ),
+                        MData::Seq(_) => Err(Error::InvalidOperation),
+                    });
+                if result.is_ok() {
+                    self.notify_mdata_subscribers(
+                        address,
+                        MDataChange::UnseqEntries(actions.clone()),
+                    );
+                }
+                Response::Mutation(result)
+            }
+            Request::ChangeMDataOwner {
+                address,
+                new_owner,
+                version,
+                ..
+            } => {
+                let result = self.mdata_mut(key, address, request).and_then(|data| {
+                    data.as_mutable_data_mut()
+                        .change_owner(new_owner.clone(), *version)
+                });
+                if result.is_ok() {
+                    self.notify_mdata_subscribers(
+                        address,
+                        MDataChange::SetOwners {
+                            owners: new_owner.clone(),
+                        },
+                    );
+                }
+                Response::Mutation(result)
+            }
+            Request::SubscribeMData(address) => match self.mdata(key, address, request) {
+                Ok(data) => {
+                    let response = match data {
+                        MData::Seq(data) => Response::GetSeqMData(Ok(data.clone())),
+                        MData::Unseq(data) => Response::GetUnseqMData(Ok(data.clone())),
+                    };
+                    let _ = self
+                        .mdata_subscriptions
+                        .entry(*address)
+                        .or_default()
+                        .insert(key);
+                    response
+                }
+                Err(error) => error_response(request, error),
+            },
+            Request::UnsubscribeMData(address) => {
+                let result = self.mdata(key, address, request).map(|_| ());
+                if result.is_ok() {
+                    if let Some(subscribers) = self.mdata_subscriptions.get_mut(address) {
+                        let _ = subscribers.remove(&key);
+                    }
+                }
+                Response::Mutation(result)
+            }
+            //
+            // ===== Append Only Data =====
+            //
+            Request::PutAData(data) => Response::Mutation(self.put_adata(key, data.clone())),
+            Request::GetAData(address) => match self.adata(key, address, request) {
+                Ok(data) => Response::GetAData(Ok(data.clone())),
+                Err(error) => error_response(request, error),
+            },
+            Request::GetADataShell {
+                address,
+                data_index,
+            } => Response::GetADataShell(self.adata(key, address, request).and_then(|data| {
+                let index = data_index.resolve(data.indices()?.data_index())?;
+                data.shell(index)
+            })),
+            Request::DeleteAData(proof) => {
+                Response::Mutation(self.delete_adata(key, proof, request))
+            }
+            Request::GetADataRange { address, range } => Response::GetADataRange(
+                self.adata(key, address, request)
+                    .and_then(|data| data.in_range(range.0, range.1)),
+            ),
+            Request::GetADataIndices(address) => Response::GetADataIndices(
+                self.adata(key, address, request).and_then(AData::indices),
+            ),
+            Request::GetADataLastEntry(address) => Response::GetADataLastEntry(
+                self.adata(key, address, request)
+                    .and_then(|data| data.last_entry().ok_or(Error::NoSuchEntry)),
+            ),
+            Request::GetADataValue {
+                address,
+                key: entry,
+            } => Response::GetADataValue(
+                self.adata(key, address, request)
+                    .and_then(|data| data.get_latest(entry).cloned().ok_or(Error::NoSuchEntry)),
+            ),
+            Request::GetADataPermissions {
+                address,
+                permissions_index,
+            } => {
+                let data = match self.adata(key, address, request) {
+                    Ok(data) => data,
+                    Err(error) => return error_response(request, error),
+                };
+                let index = match permissions_index.resolve(data.permissions_index()) {
+                    Ok(index) => index,
+                    Err(error) => return error_response(request, error),
+                };
+                match data {
+                    AData::PubSeq(data) => Response::GetPubADataPermissionAtIndex(
+                        data.fetch_permissions_at_index(index)
+                            .cloned()
+                            .ok_or(Error::NoSuchEntry),
+                    ),
+                    AData::PubUnseq(data) => Response::GetPubADataPermissionAtIndex(
+                        data.fetch_permissions_at_index(index)
+                            .cloned()
+                            .ok_or(Error::NoSuchEntry),
+                    ),
+                    AData::UnpubSeq(data) => Response::GetUnpubADataPermissionAtIndex(
+                        data.fetch_permissions_at_index(index)
+                            .cloned()
+                            .ok_or(Error::NoSuchEntry),
+                    ),
+                    AData::UnpubUnseq(data) => Response::GetUnpubADataPermissionAtIndex(
+                        data.fetch_permissions_at_index(index)
+                            .cloned()
+                            .ok_or(Error::NoSuchEntry),
+                    ),
+                }
+            }
+            Request::GetPubADataUserPermissions {
+                address,
+                permissions_index,
+                user,
+            } => Response::GetPubADataUserPermissions(self.adata(key, address, request).and_then(
+                |data| {
+                    let index = permissions_index.resolve(data.permissions_index())?;
+                    data.pub_user_permissions(user.clone(), index)
+                },
+            )),
+            Request::GetUnpubADataUserPermissions {
+                address,
+                permissions_index,
+                public_key,
+            } => Response::GetUnpubADataUserPermissions(
+                self.adata(key, address, request).and_then(|data| {
+                    let index = permissions_index.resolve(data.permissions_index())?;
+                    data.unpub_user_permissions(*public_key, index)
+                }),
+            ),
+            Request::GetADataOwners {
+                address,
+                owners_index,
+            } => Response::GetADataOwners(self.adata(key, address, request).and_then(|data| {
+                let index = owners_index.resolve(data.owners_index())?;
+                data.owners(index).cloned().ok_or(Error::NoSuchEntry)
+            })),
+            Request::AddPubADataPermissions {
+                address,
+                permissions,
+            } => Response::Mutation(self.adata_mut(key, address, request).and_then(
+                |data| match data {
+                    AData::PubSeq(data) => {
+                        data.append_permissions(permissions.clone(), &adata_limits)
+                    }
+                    AData::PubUnseq(data) => {
+                        data.append_permissions(permissions.clone(), &adata_limits)
+                    }
+                    AData::UnpubSeq(_) | AData::UnpubUnseq(_) => Err(Error::InvalidOperation),
+                },
+            )),
+            Request::AddUnpubADataPermissions {
+                address,
+                permissions,
+            } => Response::Mutation(self.adata_mut(key, address, request).and_then(
+                |data| match data {
+                    AData::UnpubSeq(data) => {
+                        data.append_permissions(permissions.clone(), &adata_limits)
+                    }
+                    AData::UnpubUnseq(data) => {
+                        data.append_permissions(permissions.clone(), &adata_limits)
+                    }
+                    AData::PubSeq(_) | AData::PubUnseq(_) => Err(Error::InvalidOperation),
+                },
+            )),
+            Request::SetADataOwner { address, owner } => Response::Mutation(
+                self.adata_mut(key, address, request)
+                    .and_then(|data| match data {
+                        AData::PubSeq(data) => data.append_owner(owner.clone(), &adata_limits),
+                        AData::PubUnseq(data) => data.append_owner(owner.clone(), &adata_limits),
+                        AData::UnpubSeq(data) => data.append_owner(owner.clone(), &adata_limits),
+                        AData::UnpubUnseq(data) => data.append_owner(owner.clone(), &adata_limits),
+                    }),
+            ),
+            Request::AppendSeq { append, index } => {
+                let result = self
+                    .adata_mut(key, &append.address, request)
+                    .and_then(|data| match data {
+                        AData::PubSeq(data) => data.append(&append.values, *index, &adata_limits),
+                        AData::UnpubSeq(data) => data.append(&append.values, *index, &adata_limits),
+                        AData::PubUnseq(_) | AData::UnpubUnseq(_) => Err(Error::InvalidOperation),
+                    });
+                if result.is_ok() {
+                    self.notify_adata_subscribers(&append.address);
+                }
+                Response::Mutation(result)
+            }
+            Request::AppendUnseq(append) => {
+                let result = self
+                    .adata_mut(key, &append.address, request)
+                    .and_then(|data| match data {
+                        AData::PubUnseq(data) => data.append(&append.values, &adata_limits),
+                        AData::UnpubUnseq(data) => data.append(&append.values, &adata_limits),
+                        AData::PubSeq(_) | AData::UnpubSeq(_) => Err(Error::InvalidOperation),
+                    });
+                if result.is_ok() {
+                    self.notify_adata_subscribers(&append.address);
+                }
+                Response::Mutation(result)
+            }
+            Request::SubscribeAData {
+                address,
+                from_index,
+            } => Response::ADataEvent(self.subscribe_adata(key, address, *from_index, request)),
+            Request::UnsubscribeAData(address) => {
+                let result = self.adata(key, address, request).map(|_| ());
+                if result.is_ok() {
+                    if let Some(subscribers) = self.adata_subscriptions.get_mut(address) {
+                        let _ = subscribers.remove(&key);
+                    }
+                }
+                Response::Mutation(result)
+            }
+            //
+            // ===== Coins =====
+            //
+            Request::TransferCoins {
+                destination,
+                amount,
+                transaction_id,
+            } => Response::Mutation(self.transfer_coins(
+                requester.name(),
+                *destination,
+                *amount,
+                *transaction_id,
+            )),
+            Request::GetTransaction {
+                coins_balance_id,
+                transaction_id,
+            } => Response::GetTransaction(Ok(match self.balances.get(coins_balance_id) {
+                Some(balance) => match balance.transactions.get(transaction_id) {
+                    Some(amount) => Transaction::Success(*amount),
+                    None => Transaction::NoSuchTransaction,
+                },
+                None => Transaction::NoSuchCoinBalance,
+            })),
+            Request::GetBalance => Response::GetBalance(
+                self.balances
+                    .get(requester.name())
+                    .map(|balance| balance.coins)
+                    .ok_or(Error::NoSuchAccount),
+            ),
+            Request::CreateCoinBalance {
+                new_balance_owner,
+                amount,
+                transaction_id,
+            } => Response::Mutation(self.create_balance(
+                requester.name(),
+                *new_balance_owner,
+                *amount,
+                *transaction_id,
+            )),
+            //
+            // ===== Account =====
+            //
+            Request::CreateAccount(account) => {
+                Response::Mutation(self.create_account(account.clone()))
+            }
+            Request::CreateAccountFor {
+                new_account_owner,
+                amount,
+                transaction_id,
+                new_account,
+            } => Response::Mutation(self.create_account_for(
+                requester.name(),
+                *new_account_owner,
+                *amount,
+                *transaction_id,
+                new_account.clone(),
+            )),
+            Request::UpdateAccount(account) => {
+                Response::Mutation(self.update_account(key, account.clone()))
+            }
+            Request::GetAccount(name) => Response::GetAccount(
+                self.accounts
+                    .get(name)
+                    .ok_or(Error::NoSuchAccount)
+                    .and_then(|account| {
+                        if *account.authorised_getter() == key {
+                            Ok((account.data().to_vec(), account.signature().clone()))
+                        } else {
+                            Err(Error::AccessDenied)
+                        }
+                    }),
+            ),
+            //
+            // ===== Client (Owner) to SrcElders =====
+            //
+            Request::ListAuthKeysAndVersion => {
+                Response::ListAuthKeysAndVersion(Ok(match self.auth_keys.get(requester.name()) {
+                    Some(auth_keys) => (auth_keys.keys.clone(), auth_keys.version),
+                    None => (Default::default(), 0),
+                }))
+            }
+            Request::InsAuthKey {
+                key: app_key,
+                version,
+                permissions,
+            } => {
+                let auth_keys = self.auth_keys.entry(*requester.name()).or_default();
+                Response::Mutation(if *version != auth_keys.version + 1 {
+                    Err(Error::InvalidSuccessor(auth_keys.version))
+                } else {
+                    let _ = auth_keys.keys.insert(*app_key, *permissions);
+                    auth_keys.version = *version;
+                    Ok(())
+                })
+            }
+            Request::DelAuthKey {
+                key: app_key,
+                version,
+            } => {
+                let auth_keys = self.auth_keys.entry(*requester.name()).or_default();
+                Response::Mutation(if *version != auth_keys.version + 1 {
+                    Err(Error::InvalidSuccessor(auth_keys.version))
+                } else if auth_keys.keys.remove(app_key).is_none() {
+                    Err(Error::NoSuchKey)
+                } else {
+                    auth_keys.version = *version;
+                    Ok(())
+                })
+            }
+            //
+            // ===== Multi-owner =====
+            //
+            // Co-signed requests can't be nested.
+            Request::CoSigned { .. } => error_response(request, Error::InvalidOperation),
+        }
+    }
+
+    // Checks that an app is authorised by its owner to perform the request.
+    fn authorise(&self, requester: &PublicId, request: &Request) -> Result<()> {
+        let app = match requester {
+            PublicId::App(app) => app,
+            PublicId::Client(_) | PublicId::Node(_) => return Ok(()),
+        };
+
+        match request.inner() {
+            Request::ListAuthKeysAndVersion
+            | Request::InsAuthKey { .. }
+            | Request::DelAuthKey { .. }
+            | Request::CreateAccount(_)
+            | Request::CreateAccountFor { .. }
+            | Request::UpdateAccount(_)
+            | Request::GetAccount(_) => return Err(Error::AccessDenied),
+            _ => (),
+        }
+
+        let permissions = self
+            .auth_keys
+            .get(app.owner_name())
+            .and_then(|auth_keys| auth_keys.keys.get(app.public_key()))
+            .ok_or(Error::AccessDenied)?;
+
+        match request.inner() {
+            Request::TransferCoins { .. } | Request::CreateCoinBalance { .. }
+                if !permissions.transfer_coins =>
+            {
+                Err(Error::AccessDenied)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn put_idata(&mut self, requester: PublicKey, data: IDataKind) -> Result<()> {
+        if let IDataKind::Unpub(ref data) = data {
+            if *data.owners() != requester {
+                return Err(Error::InvalidOwners);
+            }
+        }
+        if !data.validate_size() {
+            return Err(Error::ExceededSize);
+        }
+
+        match self.idata.entry(*data.address()) {
+            // Storing the same published chunk twice is a no-op.
+            Entry::Occupied(_) if data.published() => Ok(()),
+            Entry::Occupied(_) => Err(Error::DataExists),
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(data);
+                Ok(())
+            }
+        }
+    }
+
+    fn get_idata(&self, requester: PublicKey, address: &IDataAddress) -> Result<IDataKind> {
+        match self.idata.get(address) {
+            Some(IDataKind::Unpub(data)) if *data.owners() != requester => Err(Error::AccessDenied),
+            Some(data) => Ok(data.clone()),
+            None => Err(Error::NoSuchData),
+        }
+    }
+
+    fn delete_unpub_idata(&mut self, proof: &DeletionProof<IDataAddress>) -> Result<()> {
+        match self.idata.get(&proof.address) {
+            Some(IDataKind::Unpub(data)) => data.check_deletion(proof)?,
+            Some(IDataKind::Pub(_)) => return Err(Error::InvalidOperation),
+            None if proof.address.published() => return Err(Error::InvalidOperation),
+            None => return Err(Error::NoSuchData),
+        }
+        self.use_deletion_proof(proof)?;
+        let _ = self.idata.remove(&proof.address);
+        Ok(())
+    }
+
+    // Records the use of `proof`, unless it was used before.
+    fn use_deletion_proof<A: Serialize>(&mut self, proof: &DeletionProof<A>) -> Result<()> {
+        if self
+            .used_deletion_proofs
+            .insert(deletion_payload(&proof.address, proof.version))
+        {
+            Ok(())
+        } else {
+            Err(Error::DeletionProofUsed)
+        }
+    }
+
+    fn put_mdata(
+        &mut self,
+        requester: PublicKey,
+        address: MDataAddress,
+        owners: &OwnerSet,
+        data: MData,
+    ) -> Result<()> {
+        owners.validate()?;
+        if !owners.contains(&requester) {
+            return Err(Error::InvalidOwners);
+        }
+        data.as_mutable_data().check_limits(&self.mdata_limits)?;
+        match self.mdata.entry(address) {
+            Entry::Occupied(_) => Err(Error::DataExists),
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(data);
+                Ok(())
+            }
+        }
+    }
+
+    fn delete_mdata(
+        &mut self,
+        requester: PublicKey,
+        address: &MDataAddress,
+        request: &Request,
+    ) -> Result<()> {
+        let _ = self.mdata(requester, address, request)?;
+        let _ = self.mdata.remove(address);
+        let _ = self.mdata_subscriptions.remove(address);
+        Ok(())
+    }
+
+    // Queues a notification of `change` for every subscriber of the data. Subscribers which are
+    // no longer allowed to read the data are dropped.
+    fn notify_mdata_subscribers(&mut self, address: &MDataAddress, change: MDataChange) {
+        let (data, subscribers) = match (
+            self.mdata.get(address),
+            self.mdata_subscriptions.get_mut(address),
+        ) {
+            (Some(data), Some(subscribers)) => (data.as_mutable_data(), subscribers),
+            _ => return,
+        };
+
+        let revoked: Vec<_> = subscribers
+            .iter()
+            .filter(|subscriber| {
+                data.check_permissions(Request::SubscribeMData(*address), **subscriber)
+                    .is_err()
+            })
+            .cloned()
+            .collect();
+        for subscriber in revoked {
+            let _ = subscribers.remove(&subscriber);
+        }
+
+        let event = MDataEvent {
+            address: *address,
+            change,
+            version: data.version(),
+        };
+        for subscriber in subscribers.iter() {
+            self.notifications
+                .entry(*subscriber)
+                .or_default()
+                .push(Message::Notification {
+                    notification: Notification::MDataEvent(event.clone()),
+                    message_id: MessageId::new(),
+                });
+        }
+    }
+
+    fn mdata(
+        &self,
+        requester: PublicKey,
+        address: &MDataAddress,
+        request: &Request,
+    ) -> Result<&MData> {
+        let data = self.mdata.get(address).ok_or(Error::NoSuchData)?;
+        data.as_mutable_data()
+            .check_permissions(request.clone(), requester)?;
+        Ok(data)
+    }
+
+    fn mdata_mut(
+        &mut self,
+        requester: PublicKey,
+        address: &MDataAddress,
+        request: &Request,
+    ) -> Result<&mut MData> {
+        let data = self.mdata.get_mut(address).ok_or(Error::NoSuchData)?;
+        data.as_mutable_data()
+            .check_permissions(request.clone(), requester)?;
+        Ok(data)
+    }
+
+    fn put_adata(&mut self, requester: PublicKey, data: AData) -> Result<()> {
+        for index in 0..data.owners_index() {
+            data.owners(index)
+                .ok_or(Error::InvalidOwners)?
+                .owners
+                .validate()?;
+        }
+        let owner = data
+            .owners_index()
+            .checked_sub(1)
+            .and_then(|index| data.owners(index))
+            .ok_or(Error::InvalidOwners)?;
+        if !owner.owners.contains(&requester) {
+            return Err(Error::InvalidOwners);
+        }
+        data.check_limits(&self.adata_limits)?;
+        match self.adata.entry(*data.address()) {
+            Entry::Occupied(_) => Err(Error::DataExists),
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(data);
+                Ok(())
+            }
+        }
+    }
+
+    fn delete_adata(
+        &mut self,
+        requester: PublicKey,
+        proof: &DeletionProof<ADataAddress>,
+        request: &Request,
+    ) -> Result<()> {
+        let address = &proof.address;
+        if address.is_pub() {
+            return Err(Error::InvalidOperation);
+        }
+        let _ = self.adata(requester, address, request)?;
+        self.use_deletion_proof(proof)?;
+        let _ = self.adata.remove(address);
+        let _ = self.adata_subscriptions.remove(address);
+        Ok(())
+    }
+
+    fn subscribe_adata(
+        &mut self,
+        requester: PublicKey,
+        address: &ADataAddress,
+        from_index: u64,
+        request: &Request,
+    ) -> Result<ADataEvent> {
+        let data = self.adata(requester, address, request)?;
+        let entry_index = data.entry_index();
+        let entries = if from_index < entry_index {
+            data.in_range(ADataIndex::FromStart(from_index), ADataIndex::FromEnd(0))?
+        } else {
+            Vec::new()
+        };
+        let _ = self
+            .adata_subscriptions
+            .entry(*address)
+            .or_default()
+            .insert(requester, entry_index.max(from_index));
+        Ok(ADataEvent {
+            address: *address,
+            index: from_index,
+            entries,
+        })
+    }
+
+    // Queues a notification with the newly appended entries for every subscriber of the data.
+    // Subscribers which are no longer allowed to read the data are dropped.
+    fn notify_adata_subscribers(&mut self, address: &ADataAddress) {
+        let (data, subscribers) = match (
+            self.adata.get(address),
+            self.adata_subscriptions.get_mut(address),
+        ) {
+            (Some(data), Some(subscribers)) => (data, subscribers),
+            _ => return,
+        };
+        let entry_index = data.entry_index();
+        let mut revoked = Vec::new();
+
+        for (subscriber, next_index) in subscribers.iter_mut() {
+            if *next_index >= entry_index {
+                continue;
+            }
+            let request = Request::SubscribeAData {
+                address: *address,
+                from_index: *next_index,
+            };
+            if data.check_permission(&request, *subscriber).is_err() {
+                revoked.push(*subscriber);
+                continue;
+            }
+            let entries =
+                match data.in_range(ADataIndex::FromStart(*next_index), ADataIndex::FromEnd(0)) {
+                    Ok(entries) => entries,
+                    Err(_) => continue,
+                };
+            let event = ADataEvent {
+                address: *address,
+                index: *next_index,
+                entries,
+            };
+            self.notifications
+                .entry(*subscriber)
+                .or_default()
+                .push(Message::Notification {
+                    notification: Notification::ADataEvent(event),
+                    message_id: MessageId::new(),
+                });
+            *next_index = entry_index;
+        }
+
+        for subscriber in revoked {
+            let _ = subscribers.remove(&subscriber);
+        }
+    }
+
+    fn adata(
+        &self,
+        requester: PublicKey,
+        address: &ADataAddress,
+        request: &Request,
+    ) -> Result<&AData> {
+        let data = self.adata.get(address).ok_or(Error::NoSuchData)?;
+        data.check_permission(request, requester)?;
+        Ok(data)
+    }
+
+    fn adata_mut(
+        &mut self,
+        requester: PublicKey,
+        address: &ADataAddress,
+        request: &Request,
+    ) -> Result<&mut AData> {
+        let data = self.adata.get_mut(address).ok_or(Error::NoSuchData)?;
+        data.check_permission(request, requester)?;
+        Ok(data)
+    }
+
+    fn transfer_coins(
+        &mut self,
+        source: &XorName,
+        destination: XorName,
+        amount: Coins,
+        transaction_id: TransactionId,
+    ) -> Result<()> {
+        let remaining = self
+            .balances
+            .get(source)
+            .ok_or(Error::NoSuchAccount)?
+            .coins
+            .checked_sub(amount)
+            .ok_or(Error::InsufficientBalance)?;
+
+        let balance = self
+            .balances
+            .get(&destination)
+            .ok_or(Error::NoSuchAccount)?;
+        if balance.transactions.contains_key(&transaction_id) {
+            return Err(Error::TransactionIdExists);
+        }
+        let credited = balance
+            .coins
+            .checked_add(amount)
+            .ok_or(Error::ExcessiveValue)?;
+
+        if let Some(balance) = self.balances.get_mut(source) {
+            balance.coins = remaining;
+        }
+        if let Some(balance) = self.balances.get_mut(&destination) {
+            // For a transfer to self the debit above has to be taken into account.
+            balance.coins = if *source == destination {
+                balance.coins.checked_add(amount).unwrap_or(credited)
+            } else {
+                credited
+            };
+            let _ = balance.transactions.insert(transaction_id, amount);
+        }
+        Ok(())
+    }
+
+    fn create_balance(
+        &mut self,
+        source: &XorName,
+        owner: PublicKey,
+        amount: Coins,
+        transaction_id: TransactionId,
+    ) -> Result<()> {
+        let destination = XorName::from(owner);
+        let remaining = self
+            .balances
+            .get(source)
+            .ok_or(Error::NoSuchAccount)?
+            .coins
+            .checked_sub(amount)
+            .ok_or(Error::InsufficientBalance)?;
+        if self.balances.contains_key(&destination) {
+            return Err(Error::AccountExists);
+        }
+
+        if let Some(balance) = self.balances.get_mut(source) {
+            balance.coins = remaining;
+        }
+        let mut balance = Balance {
+            coins: amount,
+            transactions: BTreeMap::new(),
+        };
+        let _ = balance.transactions.insert(transaction_id, amount);
+        let _ = self.balances.insert(destination, balance);
+        Ok(())
+    }
+
+    fn create_account(&mut self, account: AccountData) -> Result<()> {
+        validate_account(&account)?;
+        match self.accounts.entry(*account.destination()) {
+            Entry::Occupied(_) => Err(Error::AccountExists),
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(account);
+                Ok(())
+            }
+        }
+    }
+
+    fn create_account_for(
+        &mut self,
+        source: &XorName,
+        owner: PublicKey,
+        amount: Coins,
+        transaction_id: TransactionId,
+        account: AccountData,
+    ) -> Result<()> {
+        validate_account(&account)?;
+        if self.accounts.contains_key(account.destination()) {
+            return Err(Error::AccountExists);
+        }
+        self.create_balance(source, owner, amount, transaction_id)?;
+        self.create_account(account)
+    }
+
+    fn update_account(&mut self, requester: PublicKey, account: AccountData) -> Result<()> {
+        validate_account(&account)?;
+        match self.accounts.entry(*account.destination()) {
+            Entry::Occupied(ref entry) if *entry.get().authorised_getter() != requester => {
+                Err(Error::AccessDenied)
+            }
+            Entry::Occupied(mut entry) => {
+                let _ = entry.insert(account);
+                Ok(())
+            }
+            Entry::Vacant(_) => Err(Error::NoSuchAccount),
+        }
+    }
+}
+
+/// `ChunkStore` which stores chunks in a `Vault` with `PutIData` and `GetIData` requests made on
+/// behalf of `requester`.
+pub struct VaultChunkStore<'a> {
+    pub vault: &'a mut Vault,
+    pub requester: PublicId,
+}
+
+impl ChunkStore for VaultChunkStore<'_> {
+    fn get(&mut self, name: &XorName) -> Result<ImmutableData> {
+        let request = Request::GetIData(IDataAddress::Pub(*name));
+        match self.vault.handle_request(&self.requester, request) {
+            Response::GetIData(Ok(IDataKind::Pub(data))) => Ok(data),
+            Response::GetIData(Err(error)) => Err(error),
+            _ => Err(Error::UnexpectedDataReturned),
+        }
+    }
+
+    fn put(&mut self, data: ImmutableData) -> Result<()> {
+        match self
+            .vault
+            .handle_request(&self.requester, Request::PutIData(data.into()))
+        {
+            Response::Mutation(result) => result,
+            _ => Err(Error::UnexpectedDataReturned),
+        }
+    }
+}
+
+fn validate_account(account: &AccountData) -> Result<()> {
+    if !account.size_is_valid() {
+        return Err(Error::ExceededSize);
+    }
+    account
+        .authorised_getter()
+        .verify(account.signature(), account.data())
+}
+
+// Checks the signatures of the co-signers of a `CoSigned` request over the request they approve.
+fn verify_co_signatures(request: &Request, message_id: &MessageId) -> Result<()> {
+    if let Request::CoSigned {
+        request: inner,
+        signatures,
+    } = request
+    {
+        for (key, signature) in signatures {
+            utils::verify_signature(signature, key, inner, message_id)?;
+        }
+    }
+    Ok(())
+}
+
+// Returns the response variant for `request` carrying the given error.
+fn error_response(request: &Request, error: Error) -> Response {
+    match request {
+        Request::CoSigned { request, .. } => error_response(request, error),
+        Request::GetIData(_) => Response::GetIData(Err(error)),
+        Request::GetMData(address) | Request::SubscribeMData(address) => {
+            if address.is_seq() {
+                Response::GetSeqMData(Err(error))
+            } else {
+                Response::GetUnseqMData(Err(error))
+            }
+        }
+        Request::GetMDataValue { address, .. } => {
+            if address.is_seq() {
+                Response::GetSeqMDataValue(Err(error))
+            } else {
+                Response::GetUnseqMDataValue(Err(error))
+            }
+        }
+        Request::GetMDataValueAtVersion { .. } => Response::GetMDataValueAtVersion(Err(error)),
+        Request::ListMDataValueHistory { .. } => Response::ListMDataValueHistory(Err(error)),
+        Request::GetMDataShell(address) => {
+            if address.is_seq() {
+                Response::GetSeqMDataShell(Err(error))
+            } else {
+                Response::GetUnseqMDataShell(Err(error))
+            }
+        }
+        Request::GetMDataVersion(_) => Response::GetMDataVersion(Err(error)),
+        Request::ListMDataEntries(address) => {
+            if address.is_seq() {
+                Response::ListSeqMDataEntries(Err(error))
+            } else {
+                Response::ListUnseqMDataEntries(Err(error))
+            }
+        }
+        Request::ListMDataEntriesPage { address, .. } => {
+            if address.is_seq() {
+                Response::ListSeqMDataEntriesPage(Err(error))
+            } else {
+                Response::ListUnseqMDataEntriesPage(Err(error))
+            }
+        }
+        Request::ListMDataKeysPage { .. } => Response::ListMDataKeysPage(Err(error)),
+        Request::ListMDataValuesPage { address, .. } => {
+            if address.is_seq() {
+                Response::ListSeqMDataValuesPage(Err(error))
+            } else {
+                Response::ListUnseqMDataValuesPage(Err(error))
+            }
+        }
+        Request::ListMDataKeys(_) => Response::ListMDataKeys(Err(error)),
+        Request::ListMDataValues(address) => {
+            if address.is_seq() {
+                Response::ListSeqMDataValues(Err(error))
+            } else {
+                Response::ListUnseqMDataValues(Err(error))
+            }
+        }
+        Request::ListMDataPermissions(_) => Response::ListMDataPermissions(Err(error)),
+        Request::ListMDataUserPermissions { .. } => Response::ListMDataUserPermissions(Err(error)),
+        Request::GetAData(_) => Response::GetAData(Err(error)),
+        Request::GetADataShell { .. } => Response::GetADataShell(Err(error)),
+        Request::GetADataRange { .. } => Response::GetADataRange(Err(error)),
+        Request::GetADataIndices(_) => Response::GetADataIndices(Err(error)),
+        Request::GetADataLastEntry(_) => Response::GetADataLastEntry(Err(error)),
+        Request::GetADataValue { .. } => Response::GetADataValue(Err(error)),
+        Request::GetADataPermissions { address, .. } => {
+            if address.is_pub() {
+                Response::GetPubADataPermissionAtIndex(Err(error))
+            } else {
+                Response::GetUnpubADataPermissionAtIndex(Err(error))
+            }
+        }
+        Request::GetPubADataUserPermissions { .. } => {
+            Response::GetPubADataUserPermissions(Err(error))
+        }
+        Request::GetUnpubADataUserPermissions { .. } => {
+            Response::GetUnpubADataUserPermissions(Err(error))
+        }
+        Request::GetADataOwners { .. } => Response::GetADataOwners(Err(error)),
+        Request::SubscribeAData { .. } => Response::ADataEvent(Err(error)),
+        Request::GetTransaction { .. } => Response::GetTransaction(Err(error)),
+        Request::GetBalance => Response::GetBalance(Err(error)),
+        Request::ListAuthKeysAndVersion => Response::ListAuthKeysAndVersion(Err(error)),
+        Request::GetAccount(_) => Response::GetAccount(Err(error)),
+        Request::PutIData(_)
+        | Request::DeleteUnpubIData(_)
+        | Request::PutUnseqMData(_)
+        | Request::PutSeqMData(_)
+        | Request::DeleteMData(_)
+        | Request::SetMDataUserPermissions { .. }
+        | Request::DelMDataUserPermissions { .. }
+        | Request::MutateSeqMDataEntries { .. }
+        | Request::MutateUnseqMDataEntries { .. }
+        | Request::ChangeMDataOwner { .. }
+        | Request::UnsubscribeMData(_)
+        | Request::PutAData(_)
+        | Request::DeleteAData(_)
+        | Request::AddPubADataPermissions { .. }
+        | Request::AddUnpubADataPermissions { .. }
+        | Request::SetADataOwner { .. }
+        | Request::AppendSeq { .. }
+        | Request::AppendUnseq(_)
+        | Request::UnsubscribeAData(_)
+        | Request::TransferCoins { .. }
+        | Request::CreateCoinBalance { .. }
+        | Request::CreateAccount(_)
+        | Request::CreateAccountFor { .. }
+        | Request::UpdateAccount(_)
+        | Request::InsAuthKey { .. }
+        | Request::DelAuthKey { .. } => Response::Mutation(Err(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mdata_owner_change_payload, request_payload, ADataAppend, ADataOwner,
+        ADataUnpubPermissionSet, ADataUnpubPermissions, AppFullId, ChunkReader, ChunkWriter,
+        ClientFullId, MDataAction, MDataPermissionSet, MDataSeqEntryActions,
+        MDataUnseqEntryActions, MDataUser, MDataValue, MessageId, SelfEncryptor, SignedRequest,
+        UnpubImmutableData, UnpubSeqAppendOnlyData, UnpubUnseqAppendOnlyData, MIN_CHUNK_SIZE,
+    };
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::iter;
+    use threshold_crypto::SecretKey;
+    use unwrap::unwrap;
+
+    fn sign(full_id: &ClientFullId, request: Request) -> Message {
+        SignedRequest::new_client(full_id, request, MessageId::new()).into()
+    }
+
+    fn send(vault: &mut Vault, full_id: &ClientFullId, request: Request) -> Response {
+        let message = sign(full_id, request);
+        let message_id = message.message_id();
+        let requester = PublicId::Client(full_id.public_id().clone());
+        match unwrap!(vault.handle_message(&requester, message)) {
+            Message::Response {
+                response,
+                message_id: response_id,
+            } => {
+                assert_eq!(message_id, response_id);
+                response
+            }
+            Message::Request { .. } | Message::Notification { .. } => {
+                panic!("Unexpected message")
+            }
+        }
+    }
+
+    #[test]
+    fn idata() {
+        let mut vault = Vault::new();
+        let client = ClientFullId::new_bls(&mut rand::thread_rng());
+
+        let data = ImmutableData::new(b"published".to_vec());
+        let address = *data.address();
+        match send(&mut vault, &client, Request::PutIData(data.clone().into())) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        // Published data can be fetched without a signature.
+        let message = Message::Request {
+            request: Request::GetIData(address),
+            message_id: MessageId::new(),
+            signature: None,
+        };
+        let requester = PublicId::Client(client.public_id().clone());
+        match unwrap!(vault.handle_message(&requester, message)) {
+            Message::Response {
+                response: Response::GetIData(Ok(IDataKind::Pub(fetched))),
+                ..
+            } => assert_eq!(fetched, data),
+            _ => panic!("Unexpected response"),
+        }
+
+        // Unpublished data can only be put and fetched by its owner.
+        let owner = SecretKey::random().public_key();
+        let data = UnpubImmutableData::new(b"unpublished".to_vec(), owner.into());
+        match send(&mut vault, &client, Request::PutIData(data.into())) {
+            Response::Mutation(Err(Error::InvalidOwners)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        // Owners aren't restricted to BLS keys.
+        let owner = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let data =
+            UnpubImmutableData::new(b"unpublished".to_vec(), *owner.public_id().public_key());
+        let address = *data.address();
+        match send(&mut vault, &owner, Request::PutIData(data.clone().into())) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &owner, Request::GetIData(address)) {
+            Response::GetIData(Ok(IDataKind::Unpub(fetched))) => assert_eq!(fetched, data),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &client, Request::GetIData(address)) {
+            Response::GetIData(Err(Error::AccessDenied)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        // Only the owner can approve a deletion, which anyone can then relay.
+        let delete = |signer: &ClientFullId, version| {
+            let payload = deletion_payload(&address, version);
+            Request::DeleteUnpubIData(DeletionProof {
+                address,
+                version,
+                signatures: iter::once((*signer.public_id().public_key(), signer.sign(&payload)))
+                    .collect(),
+            })
+        };
+        match send(&mut vault, &owner, delete(&client, 0)) {
+            Response::Mutation(Err(Error::InvalidOwners)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &client, delete(&owner, 0)) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &owner, Request::GetIData(address)) {
+            Response::GetIData(Err(Error::NoSuchData)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        // The proof can't delete the data again once it is recreated.
+        match send(&mut vault, &owner, Request::PutIData(data.into())) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &client, delete(&owner, 0)) {
+            Response::Mutation(Err(Error::DeletionProofUsed)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &client, delete(&owner, 1)) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn chunked_idata() {
+        let mut vault = Vault::new();
+        let client = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let requester = PublicId::Client(client.public_id().clone());
+        let content: Vec<u8> = (0..3 * MIN_CHUNK_SIZE).map(|_| rand::random()).collect();
+
+        let encryptor = unwrap!(SelfEncryptor::new().with_chunk_size(MIN_CHUNK_SIZE)).encrypted();
+        let mut writer = ChunkWriter::new(
+            VaultChunkStore {
+                vault: &mut vault,
+                requester: requester.clone(),
+            },
+            encryptor,
+        );
+        unwrap!(writer.write_all(&content));
+        let data_map = unwrap!(writer.finish());
+        assert_eq!(vault.idata.len(), 3);
+
+        let store = VaultChunkStore {
+            vault: &mut vault,
+            requester,
+        };
+        let mut reader = unwrap!(ChunkReader::new(store, &data_map));
+        let _ = unwrap!(reader.seek(SeekFrom::Start(MIN_CHUNK_SIZE as u64)));
+        let mut read = Vec::new();
+        let _ = unwrap!(reader.read_to_end(&mut read));
+        assert_eq!(read[..], content[MIN_CHUNK_SIZE..]);
+    }
+
+    #[test]
+    fn invalid_signature() {
+        let mut vault = Vault::new();
+        let client = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let other = ClientFullId::new_ed25519(&mut rand::thread_rng());
+
+        let message = sign(&other, Request::GetBalance);
+        let requester = PublicId::Client(client.public_id().clone());
+        match unwrap!(vault.handle_message(&requester, message)) {
+            Message::Response {
+                response: Response::GetBalance(Err(Error::InvalidSignature)),
+                ..
+            } => (),
+            _ => panic!("Unexpected response"),
+        }
+    }
+
+    #[test]
+    fn seq_mdata() {
+        let mut vault = Vault::new();
+        let owner = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let other = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let owner_key = *owner.public_id().public_key();
+        let other_key = *other.public_id().public_key();
+
+        let data = SeqMutableData::new(rand::random(), 100, owner_key.into());
+        let address = *data.address();
+        match send(&mut vault, &owner, Request::PutSeqMData(data)) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let actions = MDataSeqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec(), 0);
+        let mutate = Request::MutateSeqMDataEntries { address, actions };
+        match send(&mut vault, &other, mutate.clone()) {
+            Response::Mutation(Err(Error::AccessDenied)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let set_permissions = Request::SetMDataUserPermissions {
+            address,
+            user: MDataUser::Key(other_key),
+            permissions: MDataPermissionSet::new()
+                .allow(MDataAction::Read)
+                .allow(MDataAction::Insert),
+            version: 1,
+        };
+        match send(&mut vault, &owner, set_permissions) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &other, mutate) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let get_value = Request::GetMDataValue {
+            address,
+            key: b"key".to_vec(),
+        };
+        match send(&mut vault, &other, get_value) {
+            Response::GetSeqMDataValue(Ok(value)) => {
+                assert_eq!(value, MDataValue::new(b"value".to_vec(), 0))
+            }
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let get_history = Request::ListMDataValueHistory {
+            address,
+            key: b"key".to_vec(),
+        };
+        match send(&mut vault, &other, get_history) {
+            Response::ListMDataValueHistory(Ok(values)) => {
+                assert_eq!(values, vec![MDataValue::new(b"value".to_vec(), 0)])
+            }
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        match send(&mut vault, &other, Request::DeleteMData(address)) {
+            Response::Mutation(Err(Error::AccessDenied)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &owner, Request::DeleteMData(address)) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &owner, Request::GetMData(address)) {
+            Response::GetSeqMData(Err(Error::NoSuchData)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn change_mdata_owner() {
+        let mut vault = Vault::new();
+        let owner = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let new_owner = ClientFullId::new_bls(&mut rand::thread_rng());
+        let relay = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let owner_key = *owner.public_id().public_key();
+        let new_owner_key = *new_owner.public_id().public_key();
+
+        let relay_key = *relay.public_id().public_key();
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(
+            MDataUser::Key(relay_key),
+            MDataPermissionSet::new().allow(MDataAction::Read),
+        );
+        let data = unwrap!(UnseqMutableData::new_with_data(
+            rand::random(),
+            100,
+            BTreeMap::new(),
+            permissions,
+            owner_key.into(),
+            &Limits::default()
+        ));
+        let address = *data.address();
+        match send(&mut vault, &owner, Request::PutUnseqMData(data)) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        for full_id in &[&owner, &relay] {
+            match send(&mut vault, full_id, Request::SubscribeMData(address)) {
+                Response::GetUnseqMData(Ok(_)) => (),
+                x => panic!("Unexpected response: {:?}", x),
+            }
+        }
+
+        let new_owners = OwnerSet::from(new_owner_key);
+        let payload = mdata_owner_change_payload(&address, &new_owners, 1);
+        let signed_by = |id: &ClientFullId| -> BTreeMap<_, _> {
+            iter::once((*id.public_id().public_key(), id.sign(&payload))).collect()
+        };
+        let change_owner = |signatures, acceptance| Request::ChangeMDataOwner {
+            address,
+            new_owner: new_owners.clone(),
+            version: 1,
+            signatures,
+            acceptance,
+        };
+
+        // Signatures of anyone but the current owner are rejected, even when sent by the owner.
+        match send(&mut vault, &owner, change_owner(BTreeMap::new(), None)) {
+            Response::Mutation(Err(Error::InvalidOwners)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &owner, change_owner(signed_by(&relay), None)) {
+            Response::Mutation(Err(Error::InvalidOwners)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        // An acceptance has to be signed by the new owner.
+        let request = change_owner(signed_by(&owner), Some(signed_by(&relay)));
+        match send(&mut vault, &relay, request) {
+            Response::Mutation(Err(Error::InvalidOwners)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        // The approved transfer can be relayed by anyone, but only applied once.
+        let request = change_owner(signed_by(&owner), Some(signed_by(&new_owner)));
+        match send(&mut vault, &relay, request.clone()) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &relay, request) {
+            Response::Mutation(Err(Error::InvalidOwners)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        // Subscribers are told about the new owners, except the previous owner, which can no
+        // longer read the data and is unsubscribed.
+        let owner_id = PublicId::Client(owner.public_id().clone());
+        let relay_id = PublicId::Client(relay.public_id().clone());
+        assert!(vault.take_notifications(&owner_id).is_empty());
+        match vault.take_notifications(&relay_id).as_slice() {
+            [Message::Notification {
+                notification: Notification::MDataEvent(event),
+                ..
+            }] => {
+                assert_eq!(
+                    event.change,
+                    MDataChange::SetOwners {
+                        owners: new_owners.clone()
+                    }
+                );
+                assert_eq!(event.version, 1);
+            }
+            _ => panic!("Unexpected notifications"),
+        }
+        assert!(!vault.mdata_subscriptions[&address].contains(&owner_key));
+
+        match send(&mut vault, &owner, Request::GetMDataShell(address)) {
+            Response::GetUnseqMDataShell(Err(Error::AccessDenied)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &new_owner, Request::GetMDataShell(address)) {
+            Response::GetUnseqMDataShell(Ok(data)) => {
+                assert_eq!(*data.owners(), new_owners);
+                assert_eq!(data.version(), 1);
+            }
+            x => panic!("Unexpected response: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn co_signed_mdata() {
+        let mut vault = Vault::new();
+        let mut rng = rand::thread_rng();
+        let ids: Vec<_> = (0..2)
+            .map(|_| ClientFullId::new_ed25519(&mut rng))
+            .collect();
+        let owners = unwrap!(OwnerSet::new(
+            ids.iter().map(|id| *id.public_id().public_key()).collect(),
+            2
+        ));
+        let data = SeqMutableData::new(rand::random(), 100, owners);
+        let address = *data.address();
+        match send(&mut vault, &ids[0], Request::PutSeqMData(data)) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let mutation = Request::MutateSeqMDataEntries {
+            address,
+            actions: MDataSeqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec(), 0),
+        };
+        match send(&mut vault, &ids[0], mutation.clone()) {
+            Response::Mutation(Err(Error::AccessDenied)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        // The second owner approves the same request and message ID as the requester signs.
+        let co_sign = |request: &Request, message_id, signer: &ClientFullId| {
+            let co_signed = Request::CoSigned {
+                request: Box::new(request.clone()),
+                signatures: iter::once((
+                    *ids[1].public_id().public_key(),
+                    signer.sign(request_payload(request, &message_id)),
+                ))
+                .collect(),
+            };
+            SignedRequest::new_client(&ids[0], co_signed, message_id).into()
+        };
+        let requester = PublicId::Client(ids[0].public_id().clone());
+        let mut handle = |message| match unwrap!(vault.handle_message(&requester, message)) {
+            Message::Response { response, .. } => response,
+            Message::Request { .. } | Message::Notification { .. } => {
+                panic!("Unexpected message")
+            }
+        };
+        match handle(co_sign(&mutation, MessageId::new(), &ids[0])) {
+            Response::Mutation(Err(Error::InvalidSignature)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match handle(co_sign(&mutation, MessageId::new(), &ids[1])) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        let request = Request::DeleteMData(address);
+        match handle(co_sign(&request, MessageId::new(), &ids[1])) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn mdata_subscription() {
+        let mut vault = Vault::new();
+        let owner = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let reader = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let owner_id = PublicId::Client(owner.public_id().clone());
+        let reader_id = PublicId::Client(reader.public_id().clone());
+        let reader_key = *reader.public_id().public_key();
+
+        let data = UnseqMutableData::new(rand::random(), 100, owner_id.public_key().into());
+        let address = *data.address();
+        match send(&mut vault, &owner, Request::PutUnseqMData(data)) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        let set_permissions = Request::SetMDataUserPermissions {
+            address,
+            user: MDataUser::Key(reader_key),
+            permissions: MDataPermissionSet::new().allow(MDataAction::Read),
+            version: 1,
+        };
+        match send(&mut vault, &owner, set_permissions) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        // Subscribing returns the current data.
+        for full_id in &[&owner, &reader] {
+            match send(&mut vault, full_id, Request::SubscribeMData(address)) {
+                Response::GetUnseqMData(Ok(data)) => assert_eq!(data.version(), 1),
+                x => panic!("Unexpected response: {:?}", x),
+            }
+        }
+
+        let actions = MDataUnseqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec());
+        let mutate = Request::MutateUnseqMDataEntries {
+            address,
+            actions: actions.clone(),
+        };
+        match send(&mut vault, &owner, mutate) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        let expected = Notification::MDataEvent(MDataEvent {
+            address,
+            change: MDataChange::UnseqEntries(actions),
+            version: 1,
+        });
+        for subscriber in &[&owner_id, &reader_id] {
+            match vault.take_notifications(subscriber).as_slice() {
+                [Message::Notification { notification, .. }] => {
+                    assert_eq!(*notification, expected)
+                }
+                _ => panic!("Unexpected notifications"),
+            }
+        }
+
+        // Revoking the reader's permissions ends its subscription.
+        let del_permissions = Request::DelMDataUserPermissions {
+            address,
+            user: MDataUser::Key(reader_key),
+            version: 2,
+        };
+        match send(&mut vault, &owner, del_permissions) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        assert!(vault.take_notifications(&reader_id).is_empty());
+        match vault.take_notifications(&owner_id).as_slice() {
+            [Message::Notification {
+                notification: Notification::MDataEvent(event),
+                ..
+            }] => {
+                assert_eq!(
+                    event.change,
+                    MDataChange::DelUserPermissions {
+                        user: MDataUser::Key(reader_key)
+                    }
+                );
+                assert_eq!(event.version, 2);
+            }
+            _ => panic!("Unexpected notifications"),
+        }
+
+        // Unsubscribing needs read access to existing data, like subscribing.
+        match send(&mut vault, &reader, Request::UnsubscribeMData(address)) {
+            Response::Mutation(Err(Error::AccessDenied)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        let missing = MDataAddress::new_unseq(rand::random(), 100);
+        match send(&mut vault, &owner, Request::UnsubscribeMData(missing)) {
+            Response::Mutation(Err(Error::NoSuchData)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &owner, Request::UnsubscribeMData(address)) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        let mutate = Request::MutateUnseqMDataEntries {
+            address,
+            actions: MDataUnseqEntryActions::new().del(b"key".to_vec()),
+        };
+        match send(&mut vault, &owner, mutate) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        assert!(vault.take_notifications(&owner_id).is_empty());
+        assert!(vault.take_notifications(&reader_id).is_empty());
+    }
+
+    #[test]
+    fn unpub_adata() {
+        let mut vault = Vault::new();
+        let owner = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let other = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let owner_key = *owner.public_id().public_key();
+
+        let mut data = UnpubSeqAppendOnlyData::new(rand::random(), 100);
+        unwrap!(data.append_owner(
+            ADataOwner {
+                owners: owner_key.into(),
+                data_index: 0,
+                permissions_index: 0,
+            },
+            &Limits::default()
+        ));
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(
+            *other.public_id().public_key(),
+            ADataUnpubPermissionSet::new(true, false, false),
+        );
+        unwrap!(data.append_permissions(
+            ADataUnpubPermissions {
+                permissions,
+                data_index: 0,
+                owner_entry_index: 1,
+            },
+            &Limits::default()
+        ));
+        let address = *data.address();
+        match send(
+            &mut vault,
+            &owner,
+            Request::PutAData(AData::UnpubSeq(data.clone())),
+        ) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let append = ADataAppend {
+            address,
+            values: vec![(b"key".to_vec(), b"value".to_vec())],
+        };
+        match send(
+            &mut vault,
+            &other,
+            Request::AppendSeq {
+                append: append.clone(),
+                index: 0,
+            },
+        ) {
+            Response::Mutation(Err(Error::AccessDenied)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &owner, Request::AppendSeq { append, index: 0 }) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        match send(&mut vault, &other, Request::GetADataLastEntry(address)) {
+            Response::GetADataLastEntry(Ok(entry)) => {
+                assert_eq!(entry, (b"key".to_vec(), b"value".to_vec()))
+            }
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        // Deletions are approved by the current owner, but can be relayed by anyone.
+        let delete = |signer: &ClientFullId, version| {
+            let payload = deletion_payload(&address, version);
+            Request::DeleteAData(DeletionProof {
+                address,
+                version,
+                signatures: iter::once((*signer.public_id().public_key(), signer.sign(&payload)))
+                    .collect(),
+            })
+        };
+        match send(&mut vault, &owner, delete(&other, 1)) {
+            Response::Mutation(Err(Error::InvalidOwners)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &other, delete(&owner, 0)) {
+            Response::Mutation(Err(Error::InvalidOwnersSuccessor(1))) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &other, delete(&owner, 1)) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        // The proof can't delete the data again once it is recreated with the same owners index.
+        match send(&mut vault, &owner, Request::PutAData(AData::UnpubSeq(data))) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &other, delete(&owner, 1)) {
+            Response::Mutation(Err(Error::DeletionProofUsed)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn adata_subscription() {
+        let mut vault = Vault::new();
+        let owner = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let reader = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let stranger = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let reader_id = PublicId::Client(reader.public_id().clone());
+
+        let mut data = UnpubUnseqAppendOnlyData::new(rand::random(), 100);
+        unwrap!(data.append_owner(
+            ADataOwner {
+                owners: (*owner.public_id().public_key()).into(),
+                data_index: 0,
+                permissions_index: 0,
+            },
+            &Limits::default()
+        ));
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(
+            *reader.public_id().public_key(),
+            ADataUnpubPermissionSet::new(true, false, false),
+        );
+        unwrap!(data.append_permissions(
+            ADataUnpubPermissions {
+                permissions,
+                data_index: 0,
+                owner_entry_index: 1,
+            },
+            &Limits::default()
+        ));
+        let address = *data.address();
+        match send(
+            &mut vault,
+            &owner,
+            Request::PutAData(AData::UnpubUnseq(data)),
+        ) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let append =
+            |values: Vec<(Vec<u8>, Vec<u8>)>| Request::AppendUnseq(ADataAppend { address, values });
+        match send(
+            &mut vault,
+            &owner,
+            append(vec![(b"a".to_vec(), b"1".to_vec())]),
+        ) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let subscribe = Request::SubscribeAData {
+            address,
+            from_index: 0,
+        };
+        match send(&mut vault, &stranger, subscribe.clone()) {
+            Response::ADataEvent(Err(_)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        // The entries already present are returned straight away.
+        match send(&mut vault, &reader, subscribe) {
+            Response::ADataEvent(Ok(event)) => {
+                assert_eq!(event.index, 0);
+                assert_eq!(event.entries, vec![(b"a".to_vec(), b"1".to_vec())]);
+            }
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        assert!(vault.take_notifications(&reader_id).is_empty());
+
+        // Later entries are pushed.
+        let values = vec![
+            (b"b".to_vec(), b"2".to_vec()),
+            (b"c".to_vec(), b"3".to_vec()),
+        ];
+        match send(&mut vault, &owner, append(values.clone())) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        let notifications = vault.take_notifications(&reader_id);
+        assert_eq!(notifications.len(), 1);
+        match notifications[0] {
+            Message::Notification {
+                notification: Notification::ADataEvent(ref event),
+                ..
+            } => {
+                assert_eq!(event.address, address);
+                assert_eq!(
+                    event
+                        .indexed_entries()
+                        .map(|(index, _)| index)
+                        .collect::<Vec<_>>(),
+                    vec![1, 2]
+                );
+                assert_eq!(event.entries, values);
+            }
+            _ => panic!("Unexpected message"),
+        }
+        assert!(vault.take_notifications(&reader_id).is_empty());
+
+        // Unsubscribing needs read access, like subscribing.
+        match send(&mut vault, &stranger, Request::UnsubscribeAData(address)) {
+            Response::Mutation(Err(Error::NoSuchEntry)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &reader, Request::UnsubscribeAData(address)) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(
+            &mut vault,
+            &owner,
+            append(vec![(b"d".to_vec(), b"4".to_vec())]),
+        ) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        assert!(vault.take_notifications(&reader_id).is_empty());
+    }
+
+    #[test]
+    fn coins() {
+        let mut vault = Vault::new();
+        let client = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let new_owner = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let new_owner_key = *new_owner.public_id().public_key();
+
+        unwrap!(vault.credit(
+            *client.public_id().public_key(),
+            unwrap!(Coins::from_nano(10))
+        ));
+
+        let create_balance = Request::CreateCoinBalance {
+            new_balance_owner: new_owner_key,
+            amount: unwrap!(Coins::from_nano(3)),
+            transaction_id: 1,
+        };
+        match send(&mut vault, &client, create_balance) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let transfer = Request::TransferCoins {
+            destination: XorName::from(new_owner_key),
+            amount: unwrap!(Coins::from_nano(8)),
+            transaction_id: 2,
+        };
+        match send(&mut vault, &client, transfer) {
+            Response::Mutation(Err(Error::InsufficientBalance)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let transfer = Request::TransferCoins {
+            destination: XorName::from(new_owner_key),
+            amount: unwrap!(Coins::from_nano(7)),
+            transaction_id: 2,
+        };
+        match send(&mut vault, &client, transfer) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        match send(&mut vault, &new_owner, Request::GetBalance) {
+            Response::GetBalance(Ok(coins)) => assert_eq!(coins, unwrap!(Coins::from_nano(10))),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &client, Request::GetBalance) {
+            Response::GetBalance(Ok(coins)) => assert_eq!(coins, unwrap!(Coins::from_nano(0))),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let get_transaction = Request::GetTransaction {
+            coins_balance_id: XorName::from(new_owner_key),
+            transaction_id: 2,
+        };
+        match send(&mut vault, &client, get_transaction) {
+            Response::GetTransaction(Ok(Transaction::Success(coins))) => {
+                assert_eq!(coins, unwrap!(Coins::from_nano(7)))
+            }
+            x => panic!("Unexpected response: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn app_authorisation() {
+        let mut vault = Vault::new();
+        let mut rng = rand::thread_rng();
+        let client = ClientFullId::new_ed25519(&mut rng);
+        let app = AppFullId::new_ed25519(&mut rng, client.public_id().clone());
+        let app_id = PublicId::App(app.public_id().clone());
+
+        match vault.handle_request(&app_id, Request::GetBalance) {
+            Response::GetBalance(Err(Error::AccessDenied)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        let ins_auth_key = Request::InsAuthKey {
+            key: *app.public_id().public_key(),
+            version: 1,
+            permissions: AppPermissions {
+                transfer_coins: false,
+            },
+        };
+        match vault.handle_request(&app_id, ins_auth_key.clone()) {
+            Response::Mutation(Err(Error::AccessDenied)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        match send(&mut vault, &client, ins_auth_key) {
+            Response::Mutation(Ok(())) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+
+        unwrap!(vault.credit(
+            *client.public_id().public_key(),
+            unwrap!(Coins::from_nano(1))
+        ));
+        match vault.handle_request(&app_id, Request::GetBalance) {
+            Response::GetBalance(Ok(coins)) => assert_eq!(coins, unwrap!(Coins::from_nano(1))),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+        let transfer = Request::TransferCoins {
+            destination: *client.public_id().name(),
+            amount: unwrap!(Coins::from_nano(1)),
+            transaction_id: 1,
+        };
+        match vault.handle_request(&app_id, transfer) {
+            Response::Mutation(Err(Error::AccessDenied)) => (),
+            x => panic!("Unexpected response: {:?}", x),
+        }
+    }
+}
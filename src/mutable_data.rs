@@ -7,14 +7,42 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use crate::{utils, EntryError, Error, PublicKey, Request, Result, XorName};
+mod unseq_replica;
+
+pub use self::unseq_replica::{Timestamp, UnseqReplica};
+use crate::{
+    utils, EntryError, Error, Limits, OwnerSet, PublicKey, Request, Result, Signature, Verifiable,
+    VerificationError, XorName,
+};
+use bincode::serialized_size;
 use multibase::Decodable;
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp,
     collections::{btree_map::Entry, BTreeMap, BTreeSet},
-    mem,
+    iter, mem, result, u64,
 };
 
+/// Maximum number of items in a page of a paginated listing. Larger limits are reduced to it.
+pub const MAX_MUTABLE_DATA_PAGE_SIZE: u64 = 100;
+
+// Size of an entry value, as checked against `Limits::max_value_size`.
+trait ValueSize {
+    fn value_size(&self) -> u64;
+}
+
+impl ValueSize for Vec<u8> {
+    fn value_size(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+impl ValueSize for Value {
+    fn value_size(&self) -> u64 {
+        self.data.len() as u64
+    }
+}
+
 /// Mutable data that is unpublished on the network. This data can only be fetched by the owners or
 /// those in the permissions fields with `Permission::Read` access.
 #[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
@@ -24,12 +52,16 @@ pub struct SeqMutableData {
     /// Key-Value semantics.
     data: BTreeMap<Vec<u8>, Value>,
     /// Maps an application key to a list of allowed or forbidden actions.
-    permissions: BTreeMap<PublicKey, PermissionSet>,
+    permissions: BTreeMap<User, PermissionSet>,
     /// Version should be increased for any changes to MutableData fields except for data.
     version: u64,
     /// Contains a set of owners of this data. DataManagers enforce that a mutation request is
     /// coming from the MaidManager Authority of the Owner.
-    owners: PublicKey,
+    owners: OwnerSet,
+    /// Previous values of each entry, oldest first.
+    history: BTreeMap<Vec<u8>, Vec<Value>>,
+    /// Maximum number of previous values kept per entry. History is disabled when zero.
+    history_depth: u64,
 }
 
 /// A value in `Sequenced MutableData`
@@ -67,44 +99,210 @@ pub struct UnseqMutableData {
     /// Key-Value semantics.
     data: BTreeMap<Vec<u8>, Vec<u8>>,
     /// Maps an application key to a list of allowed or forbidden actions.
-    permissions: BTreeMap<PublicKey, PermissionSet>,
+    permissions: BTreeMap<User, PermissionSet>,
     /// Version should be increased for any changes to MutableData fields except for data.
     version: u64,
     /// Contains a set of owners of this data. DataManagers enforce that a mutation request is
     /// coming from the MaidManager Authority of the Owner.
-    owners: PublicKey,
+    owners: OwnerSet,
+}
+
+/// A user of a Mutable Data, who can be granted permissions.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum User {
+    /// Any user. The permissions of a specific key take precedence over these.
+    Anyone,
+    /// The owner of the given key.
+    Key(PublicKey),
+}
+
+impl From<PublicKey> for User {
+    fn from(key: PublicKey) -> Self {
+        User::Key(key)
+    }
 }
 
 /// Set of user permissions.
+///
+/// Actions are allowed or denied either on the whole data or only on the entries whose key starts
+/// with a given prefix. A deny always overrides an allow of the same action.
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 pub struct PermissionSet {
-    permissions: BTreeSet<Action>,
+    /// Allowed actions, by the key prefix they are scoped to. The empty prefix covers all keys.
+    allowed: BTreeMap<Vec<u8>, BTreeSet<Action>>,
+    /// Denied actions, by the key prefix they are scoped to.
+    denied: BTreeMap<Vec<u8>, BTreeSet<Action>>,
 }
 
 impl PermissionSet {
     /// Construct new permission set.
     pub fn new() -> PermissionSet {
         PermissionSet {
-            permissions: Default::default(),
+            allowed: Default::default(),
+            denied: Default::default(),
         }
     }
 
     /// Allow the given action.
-    pub fn allow(mut self, action: Action) -> Self {
-        let _ = self.permissions.insert(action);
+    pub fn allow(self, action: Action) -> Self {
+        self.allow_for_prefix(action, Vec::new())
+    }
+
+    /// Deny the given action, overriding any allow of it.
+    pub fn deny(self, action: Action) -> Self {
+        self.deny_for_prefix(action, Vec::new())
+    }
+
+    /// Allow the given action on the entries whose key starts with `prefix`.
+    pub fn allow_for_prefix(mut self, action: Action, prefix: Vec<u8>) -> Self {
+        let _ = self.allowed.entry(prefix).or_default().insert(action);
         self
     }
 
-    /// Deny the given action.
-    pub fn deny(mut self, action: Action) -> Self {
-        let _ = self.permissions.remove(&action);
+    /// Deny the given action on the entries whose key starts with `prefix`, overriding any allow
+    /// of it.
+    pub fn deny_for_prefix(mut self, action: Action, prefix: Vec<u8>) -> Self {
+        let _ = self.denied.entry(prefix).or_default().insert(action);
         self
     }
 
-    /// Is the given action allowed according to this permission set?
+    /// Is the given action allowed on the whole data according to this permission set?
     pub fn is_allowed(&self, action: Action) -> bool {
-        self.permissions.contains(&action)
+        self.check(action, None) == Some(true)
+    }
+
+    /// Is the given action allowed on the entry with the given key according to this permission
+    /// set?
+    pub fn is_allowed_for_key(&self, action: Action, key: &[u8]) -> bool {
+        self.check(action, Some(key)) == Some(true)
+    }
+
+    /// Returns `Some(false)` if the action is denied, `Some(true)` if it is allowed and `None` if
+    /// this permission set doesn't mention it.
+    ///
+    /// Without a key, only allows on the whole data count, while a deny counts whatever its
+    /// scope, as the action could otherwise reach the entries it covers.
+    pub fn check(&self, action: Action, key: Option<&[u8]>) -> Option<bool> {
+        let covers = |prefix: &Vec<u8>| match key {
+            Some(key) => key.starts_with(prefix),
+            None => prefix.is_empty(),
+        };
+        if self
+            .denied
+            .iter()
+            .any(|(prefix, actions)| actions.contains(&action) && (key.is_none() || covers(prefix)))
+        {
+            Some(false)
+        } else if self
+            .allowed
+            .iter()
+            .any(|(prefix, actions)| actions.contains(&action) && covers(prefix))
+        {
+            Some(true)
+        } else {
+            None
+        }
+    }
+}
+
+// Checks whether `requester` may perform `action`, on the entry with the given key or, without a
+// key, on the whole data. The permissions of the requester's key take precedence over those of
+// `User::Anyone`.
+fn is_allowed(
+    permissions: &BTreeMap<User, PermissionSet>,
+    requester: PublicKey,
+    action: Action,
+    key: Option<&[u8]>,
+) -> bool {
+    let check = |user| {
+        permissions
+            .get(&user)
+            .and_then(|permissions| permissions.check(action, key))
+    };
+    check(User::Key(requester))
+        .or_else(|| check(User::Anyone))
+        .unwrap_or(false)
+}
+
+/// Reason why an action is denied to a key which doesn't satisfy the owners.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Denial {
+    /// Neither the key nor `User::Anyone` has permissions.
+    NoEntry,
+    /// The action is explicitly denied by the permissions of the given user.
+    Denied(User),
+    /// Neither the permissions of the key nor those of `User::Anyone` allow the action.
+    NotAllowed,
+    /// The action is only allowed on the entries whose key starts with one of these prefixes.
+    OnlyForPrefixes(BTreeSet<Vec<u8>>),
+}
+
+/// Actions a key can perform on the whole of a Mutable Data, as enforced by
+/// `MutableData::check_permissions`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EffectivePermissions {
+    /// Actions the key is allowed to perform.
+    pub allowed: BTreeSet<Action>,
+    /// Actions the key is denied, with the reason of each denial.
+    pub denied: BTreeMap<Action, Denial>,
+}
+
+// Checks every action for `key` on the whole data, the same way `is_allowed` does.
+fn effective_permissions(
+    owners: &OwnerSet,
+    permissions: &BTreeMap<User, PermissionSet>,
+    key: PublicKey,
+) -> EffectivePermissions {
+    // In order of precedence.
+    let sets: Vec<_> = [User::Key(key), User::Anyone]
+        .iter()
+        .filter_map(|user| permissions.get(user).map(|set| (*user, set)))
+        .collect();
+
+    let mut result = EffectivePermissions::default();
+    for action in &[
+        Action::Read,
+        Action::Insert,
+        Action::Update,
+        Action::Delete,
+        Action::ManagePermissions,
+    ] {
+        let check = if owners.is_satisfied_by(&[key]) {
+            Ok(())
+        } else if sets.is_empty() {
+            Err(Denial::NoEntry)
+        } else {
+            let decision = sets
+                .iter()
+                .find_map(|(user, set)| set.check(*action, None).map(|allowed| (*user, allowed)));
+            match decision {
+                Some((_, true)) => Ok(()),
+                Some((user, false)) => Err(Denial::Denied(user)),
+                None => {
+                    let prefixes: BTreeSet<_> = sets
+                        .iter()
+                        .flat_map(|(_, set)| set.allowed.iter())
+                        .filter(|(prefix, actions)| !prefix.is_empty() && actions.contains(action))
+                        .map(|(prefix, _)| prefix.clone())
+                        .collect();
+                    if prefixes.is_empty() {
+                        Err(Denial::NotAllowed)
+                    } else {
+                        Err(Denial::OnlyForPrefixes(prefixes))
+                    }
+                }
+            }
+        };
+        match check {
+            Ok(()) => {
+                let _ = result.allowed.insert(*action);
+            }
+            Err(denial) => {
+                let _ = result.denied.insert(*action, denial);
+            }
+        }
     }
+    result
 }
 
 /// Set of Actions that can be performed on the Data
@@ -122,6 +320,76 @@ pub enum Action {
     ManagePermissions,
 }
 
+/// Parameters of a paginated listing of entries, keys or values.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PageQuery {
+    /// Key to start listing from (inclusive), usually the `next` key of the previous page.
+    pub start: Option<Vec<u8>>,
+    /// Maximum number of items in the page. Has to be non-zero, and is capped at
+    /// `MAX_MUTABLE_DATA_PAGE_SIZE`.
+    pub limit: u64,
+    /// Only list entries whose key starts with this prefix.
+    pub prefix: Option<Vec<u8>>,
+}
+
+impl PageQuery {
+    /// Construct a query for the first page of at most `limit` items.
+    pub fn new(limit: u64) -> Self {
+        PageQuery {
+            start: None,
+            limit,
+            prefix: None,
+        }
+    }
+
+    /// Start listing from the given key.
+    pub fn starting_at(mut self, key: Vec<u8>) -> Self {
+        self.start = Some(key);
+        self
+    }
+
+    /// Only list entries whose key starts with `prefix`.
+    pub fn with_prefix(mut self, prefix: Vec<u8>) -> Self {
+        self.prefix = Some(prefix);
+        self
+    }
+}
+
+/// A page of a paginated listing.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// Items in this page, in key order.
+    pub items: T,
+    /// Key to start the next page from, or `None` if this is the last page.
+    pub next: Option<Vec<u8>>,
+}
+
+// Returns up to `query.limit` entries matching the query, and the key of the following matching
+// entry, if any.
+#[allow(clippy::type_complexity)]
+fn page_of<'a, V>(
+    data: &'a BTreeMap<Vec<u8>, V>,
+    query: &PageQuery,
+) -> Result<(Vec<(&'a Vec<u8>, &'a V)>, Option<Vec<u8>>)> {
+    // An empty page would point back at its own start, so a client following `next` would never
+    // finish.
+    if query.limit == 0 {
+        return Err(Error::InvalidOperation);
+    }
+    let limit = cmp::min(query.limit, MAX_MUTABLE_DATA_PAGE_SIZE);
+    let prefix = query.prefix.as_ref().map_or(&[][..], |prefix| &prefix[..]);
+    let start = match query.start {
+        Some(ref start) if start[..] > *prefix => start.clone(),
+        _ => prefix.to_vec(),
+    };
+    let mut matching = data
+        .range(start..)
+        .take_while(|(key, _)| key.starts_with(prefix));
+    let items: Vec<_> = matching.by_ref().take(limit as usize).collect();
+    let next = matching.next().map(|(key, _)| key.clone());
+    Ok((items, next))
+}
+
 /// Defines common functions in both sequenced and unsequenced types
 pub trait MutableData {
     fn address(&self) -> &Address;
@@ -132,47 +400,137 @@ pub trait MutableData {
 
     fn version(&self) -> u64;
 
-    fn owners(&self) -> &PublicKey;
+    fn owners(&self) -> &OwnerSet;
 
     fn keys(&self) -> BTreeSet<Vec<u8>>;
 
-    fn permissions(&self) -> BTreeMap<PublicKey, PermissionSet>;
+    fn keys_page(&self, query: &PageQuery) -> Result<Page<BTreeSet<Vec<u8>>>>;
+
+    fn permissions(&self) -> BTreeMap<User, PermissionSet>;
 
-    fn user_permissions(&self, user: PublicKey) -> Result<&PermissionSet>;
+    fn user_permissions(&self, user: User) -> Result<&PermissionSet>;
+
+    fn serialised_size(&self) -> u64;
+
+    fn check_limits(&self, limits: &Limits) -> Result<()>;
 
     fn check_permissions(&self, rpc: Request, requester: PublicKey) -> Result<()>;
 
+    fn check_owner_signatures(
+        &self,
+        data: &[u8],
+        signatures: &BTreeMap<PublicKey, Signature>,
+    ) -> Result<()>;
+
     fn set_user_permissions(
         &mut self,
-        user: PublicKey,
+        user: User,
         permissions: PermissionSet,
         version: u64,
+        limits: &Limits,
     ) -> Result<()>;
 
-    fn del_user_permissions(&mut self, user: PublicKey, version: u64) -> Result<()>;
+    fn del_user_permissions(&mut self, user: User, version: u64) -> Result<()>;
 
-    fn del_user_permissions_without_validation(&mut self, user: PublicKey, version: u64) -> bool;
+    fn del_user_permissions_without_validation(&mut self, user: User, version: u64) -> bool;
 
-    fn change_owner(&mut self, new_owner: PublicKey, version: u64) -> Result<()>;
+    fn change_owner(&mut self, new_owners: OwnerSet, version: u64) -> Result<()>;
 
-    fn change_owner_without_validation(&mut self, new_owner: PublicKey, version: u64) -> bool;
+    fn change_owner_without_validation(&mut self, new_owners: OwnerSet, version: u64) -> bool;
 
     fn is_action_allowed(&self, requester: &PublicKey, action: Action) -> bool;
+
+    fn is_action_allowed_for_key(&self, requester: &PublicKey, action: Action, key: &[u8]) -> bool;
+
+    fn effective_permissions(&self, key: PublicKey) -> EffectivePermissions;
+}
+
+/// Permission changes between two versions of a Mutable Data.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug, Default)]
+pub struct PermissionsDiff {
+    /// Users whose permissions were inserted or updated, with their new permissions.
+    pub set: BTreeMap<User, PermissionSet>,
+    /// Users whose permissions were removed.
+    pub deleted: BTreeSet<User>,
+    /// Version of the data after the changes.
+    pub version: u64,
+}
+
+/// Returns the bytes which the owners sign to approve changing the owners of the data at `address`
+/// to `new_owner` at `version`.
+pub fn owner_change_payload(address: &Address, new_owner: &OwnerSet, version: u64) -> Vec<u8> {
+    utils::serialise(&(address, new_owner, version))
 }
 
 macro_rules! impl_mutable_data {
     ($flavour:ident) => {
         impl $flavour {
-            /// Returns the Shell of the data
-            pub fn shell(&self) -> Self {
-                Self {
-                    address: self.address,
-                    data: BTreeMap::new(),
-                    permissions: self.permissions.clone(),
-                    version: self.version,
-                    owners: self.owners,
+            /// Checks the approval of a change of the owners to `new_owner` at `version`.
+            ///
+            /// `signatures` of the current owners over `owner_change_payload` have to satisfy
+            /// their signing policy, and so do the `acceptance` signatures of the new owners, if
+            /// given.
+            pub fn check_owner_change(
+                &self,
+                new_owner: &OwnerSet,
+                version: u64,
+                signatures: &BTreeMap<PublicKey, Signature>,
+                acceptance: Option<&BTreeMap<PublicKey, Signature>>,
+            ) -> Result<()> {
+                let payload = owner_change_payload(&self.address, new_owner, version);
+                self.owners.verify(&payload, signatures)?;
+                match acceptance {
+                    Some(acceptance) => new_owner.verify(&payload, acceptance),
+                    None => Ok(()),
+                }
+            }
+
+            /// Computes the permission changes which turn the permissions of `self` into those of
+            /// `other`.
+            pub fn permissions_diff(&self, other: &Self) -> PermissionsDiff {
+                PermissionsDiff {
+                    set: other
+                        .permissions
+                        .iter()
+                        .filter(|(user, permissions)| {
+                            self.permissions.get(user) != Some(permissions)
+                        })
+                        .map(|(user, permissions)| (*user, permissions.clone()))
+                        .collect(),
+                    deleted: self
+                        .permissions
+                        .keys()
+                        .filter(|user| !other.permissions.contains_key(user))
+                        .cloned()
+                        .collect(),
+                    version: other.version,
                 }
             }
+
+            /// Applies permission changes computed by `permissions_diff` and moves the data to the
+            /// version of the diff.
+            ///
+            /// Returns `Error::InvalidSuccessor` if the diff doesn't lead to a later version, and
+            /// `Error::NoSuchKey` if it deletes permissions which don't exist. Nothing is applied
+            /// in either case.
+            pub fn apply_permissions_diff(&mut self, diff: PermissionsDiff) -> Result<()> {
+                if diff.version <= self.version {
+                    return Err(Error::InvalidSuccessor(self.version));
+                }
+                if diff
+                    .deleted
+                    .iter()
+                    .any(|user| !self.permissions.contains_key(user))
+                {
+                    return Err(Error::NoSuchKey);
+                }
+                for user in &diff.deleted {
+                    let _ = self.permissions.remove(user);
+                }
+                self.permissions.extend(diff.set);
+                self.version = diff.version;
+                Ok(())
+            }
         }
 
         impl MutableData for $flavour {
@@ -196,8 +554,8 @@ macro_rules! impl_mutable_data {
                 self.version
             }
 
-            /// Returns the owner key
-            fn owners(&self) -> &PublicKey {
+            /// Returns the owners
+            fn owners(&self) -> &OwnerSet {
                 &self.owners
             }
 
@@ -206,44 +564,124 @@ macro_rules! impl_mutable_data {
                 self.data.keys().cloned().collect()
             }
 
+            /// Returns a page of keys matching the query
+            fn keys_page(&self, query: &PageQuery) -> Result<Page<BTreeSet<Vec<u8>>>> {
+                let (items, next) = page_of(&self.data, query)?;
+                Ok(Page {
+                    items: items.into_iter().map(|(key, _)| key.clone()).collect(),
+                    next,
+                })
+            }
+
             /// Gets a complete list of permissions
-            fn permissions(&self) -> BTreeMap<PublicKey, PermissionSet> {
+            fn permissions(&self) -> BTreeMap<User, PermissionSet> {
                 self.permissions.clone()
             }
 
-            fn user_permissions(&self, user: PublicKey) -> Result<&PermissionSet> {
+            fn user_permissions(&self, user: User) -> Result<&PermissionSet> {
                 self.permissions.get(&user).ok_or(Error::NoSuchKey)
             }
 
+            /// Returns the size of the serialised data
+            fn serialised_size(&self) -> u64 {
+                serialized_size(self).unwrap_or(u64::MAX)
+            }
+
+            /// Checks the number and size of entries, and the total size, against `limits`
+            fn check_limits(&self, limits: &Limits) -> Result<()> {
+                if self.data.len() as u64 > limits.max_entries {
+                    return Err(Error::TooManyEntries);
+                }
+                if self.data.iter().any(|(key, value)| {
+                    key.len() as u64 > limits.max_key_size
+                        || value.value_size() > limits.max_value_size
+                }) {
+                    return Err(Error::ExceededSize);
+                }
+                if self.serialised_size() > limits.max_size {
+                    return Err(Error::ExceededSize);
+                }
+                Ok(())
+            }
+
             fn check_permissions(&self, request: Request, requester: PublicKey) -> Result<()> {
-                if self.owners == requester {
+                // The co-signatures are expected to be verified by the caller, like the
+                // requester's own signature.
+                let co_signers = request.co_signers();
+                let request = match request {
+                    Request::CoSigned { request, .. } => *request,
+                    request => request,
+                };
+                // Owner changes are approved by the signatures they carry, whoever sends them.
+                if let Request::ChangeMDataOwner {
+                    ref new_owner,
+                    version,
+                    ref signatures,
+                    ref acceptance,
+                    ..
+                } = request
+                {
+                    return self.check_owner_change(
+                        new_owner,
+                        version,
+                        signatures,
+                        acceptance.as_ref(),
+                    );
+                }
+                if self
+                    .owners
+                    .is_satisfied_by(iter::once(&requester).chain(&co_signers))
+                {
+                    return Ok(());
+                }
+                let allowed = match (required_action(&request)?, entry_key(&request)) {
+                    (Some(action), Some(key)) => {
+                        self.is_action_allowed_for_key(&requester, action, key)
+                    }
+                    (Some(action), None) => self.is_action_allowed(&requester, action),
+                    (None, _) => true,
+                };
+                if allowed {
                     Ok(())
                 } else {
-                    check_permissions_for_key(
-                        self.user_permissions(requester)
-                            .map_err(|_| Error::AccessDenied)?,
-                        request,
-                    )
+                    Err(Error::AccessDenied)
                 }
             }
 
-            /// Insert or update permissions for the provided user.
+            /// Checks that `signatures` over `data` satisfy the owners' signing policy.
+            fn check_owner_signatures(
+                &self,
+                data: &[u8],
+                signatures: &BTreeMap<PublicKey, Signature>,
+            ) -> Result<()> {
+                self.owners.verify(data, signatures)
+            }
+
+            /// Insert or update permissions for the provided user, within `limits`.
             fn set_user_permissions(
                 &mut self,
-                user: PublicKey,
+                user: User,
                 permissions: PermissionSet,
                 version: u64,
+                limits: &Limits,
             ) -> Result<()> {
                 if version != self.version + 1 {
                     return Err(Error::InvalidSuccessor(self.version));
                 }
-                let _prev = self.permissions.insert(user, permissions);
+                let prev = self.permissions.insert(user, permissions);
+                if let Err(error) = self.check_limits(limits) {
+                    let _ = match prev {
+                        Some(prev) => self.permissions.insert(user, prev),
+                        None => self.permissions.remove(&user),
+                    };
+                    return Err(error);
+                }
                 self.version = version;
                 Ok(())
             }
 
             /// Delete permissions for the provided user.
-            fn del_user_permissions(&mut self, user: PublicKey, version: u64) -> Result<()> {
+            fn del_user_permissions(&mut self, user: User, version: u64) -> Result<()> {
                 if version != self.version + 1 {
                     return Err(Error::InvalidSuccessor(self.version));
                 }
@@ -258,7 +696,7 @@ macro_rules! impl_mutable_data {
             /// Delete user permissions without performing any validation.
             fn del_user_permissions_without_validation(
                 &mut self,
-                user: PublicKey,
+                user: User,
                 version: u64,
             ) -> bool {
                 if version <= self.version {
@@ -269,69 +707,83 @@ macro_rules! impl_mutable_data {
                 true
             }
 
-            /// Change owner of the mutable data.
-            fn change_owner(&mut self, new_owner: PublicKey, version: u64) -> Result<()> {
+            /// Change owners of the mutable data.
+            fn change_owner(&mut self, new_owners: OwnerSet, version: u64) -> Result<()> {
                 if version != self.version + 1 {
                     return Err(Error::InvalidSuccessor(self.version));
                 }
-                self.owners = new_owner;
+                self.owners = new_owners;
                 self.version = version;
                 Ok(())
             }
 
-            /// Change the owner without performing any validation.
+            /// Change the owners without performing any validation.
             fn change_owner_without_validation(
                 &mut self,
-                new_owner: PublicKey,
+                new_owners: OwnerSet,
                 version: u64,
             ) -> bool {
                 if version <= self.version {
                     return false;
                 }
 
-                self.owners = new_owner;
+                self.owners = new_owners;
                 self.version = version;
                 true
             }
 
+            /// Checks whether `requester` may perform `action` on the whole data.
             fn is_action_allowed(&self, requester: &PublicKey, action: Action) -> bool {
-                match self.permissions.get(requester) {
-                    Some(perms) => perms.is_allowed(action),
-                    None => false,
-                }
+                is_allowed(&self.permissions, *requester, action, None)
+            }
+
+            /// Checks whether `requester` may perform `action` on the entry with the given key.
+            fn is_action_allowed_for_key(
+                &self,
+                requester: &PublicKey,
+                action: Action,
+                key: &[u8],
+            ) -> bool {
+                is_allowed(&self.permissions, *requester, action, Some(key))
+            }
+
+            /// Returns the actions `key` can perform on the whole data, and why it can't perform
+            /// the others.
+            fn effective_permissions(&self, key: PublicKey) -> EffectivePermissions {
+                effective_permissions(&self.owners, &self.permissions, key)
             }
         }
     };
 }
 
-fn check_permissions_for_key(permissions: &PermissionSet, request: Request) -> Result<()> {
-    match request {
+// Returns the action a non-owner needs to be allowed to make the request, or `None` if the request
+// is checked entry by entry later. The action is needed on the whole data, unless the request
+// only reads the entry returned by `entry_key`.
+fn required_action(request: &Request) -> Result<Option<Action>> {
+    match *request {
         Request::GetMData { .. }
         | Request::GetMDataShell { .. }
         | Request::GetMDataVersion { .. }
         | Request::ListMDataKeys { .. }
         | Request::ListMDataEntries { .. }
         | Request::ListMDataValues { .. }
+        | Request::ListMDataEntriesPage { .. }
+        | Request::ListMDataKeysPage { .. }
+        | Request::ListMDataValuesPage { .. }
         | Request::GetMDataValue { .. }
+        | Request::GetMDataValueAtVersion { .. }
+        | Request::ListMDataValueHistory { .. }
         | Request::ListMDataPermissions { .. }
-        | Request::ListMDataUserPermissions { .. } => {
-            if permissions.is_allowed(Action::Read) {
-                Ok(())
-            } else {
-                Err(Error::AccessDenied)
-            }
-        }
+        | Request::ListMDataUserPermissions { .. }
+        | Request::SubscribeMData { .. }
+        | Request::UnsubscribeMData { .. } => Ok(Some(Action::Read)),
 
         Request::SetMDataUserPermissions { .. } | Request::DelMDataUserPermissions { .. } => {
-            if permissions.is_allowed(Action::ManagePermissions) {
-                Ok(())
-            } else {
-                Err(Error::AccessDenied)
-            }
+            Ok(Some(Action::ManagePermissions))
         }
 
         // Mutation permissions are checked later
-        Request::MutateSeqMDataEntries { .. } | Request::MutateUnseqMDataEntries { .. } => Ok(()),
+        Request::MutateSeqMDataEntries { .. } | Request::MutateUnseqMDataEntries { .. } => Ok(None),
 
         Request::DeleteMData { .. } => Err(Error::AccessDenied),
 
@@ -339,13 +791,23 @@ fn check_permissions_for_key(permissions: &PermissionSet, request: Request) -> R
     }
 }
 
+// Returns the key of the single entry the request reads, if it reads only one.
+fn entry_key(request: &Request) -> Option<&[u8]> {
+    match *request {
+        Request::GetMDataValue { ref key, .. }
+        | Request::GetMDataValueAtVersion { ref key, .. }
+        | Request::ListMDataValueHistory { ref key, .. } => Some(key),
+        _ => None,
+    }
+}
+
 impl_mutable_data!(SeqMutableData);
 impl_mutable_data!(UnseqMutableData);
 
 /// Implements functions which are COMMON for both the mutable data
 impl UnseqMutableData {
     /// Create a new Unsequenced Mutable Data
-    pub fn new(name: XorName, tag: u64, owners: PublicKey) -> Self {
+    pub fn new(name: XorName, tag: u64, owners: OwnerSet) -> Self {
         Self {
             address: Address::new_unseq(name, tag),
             data: Default::default(),
@@ -355,23 +817,43 @@ impl UnseqMutableData {
         }
     }
 
-    /// Create a new Unsequenced Mutable Data with entries and permissions
+    /// Create a new Unsequenced Mutable Data with entries and permissions, within `limits`
     pub fn new_with_data(
         name: XorName,
         tag: u64,
         data: BTreeMap<Vec<u8>, Vec<u8>>,
-        permissions: BTreeMap<PublicKey, PermissionSet>,
-        owners: PublicKey,
-    ) -> Self {
-        Self {
+        permissions: BTreeMap<User, PermissionSet>,
+        owners: OwnerSet,
+        limits: &Limits,
+    ) -> Result<Self> {
+        let data = Self {
             address: Address::new_unseq(name, tag),
             data,
             permissions,
             version: 0,
             owners,
+        };
+        data.check_limits(limits)?;
+        Ok(data)
+    }
+
+    /// Returns the Shell of the data
+    pub fn shell(&self) -> Self {
+        Self {
+            address: self.address,
+            data: BTreeMap::new(),
+            permissions: self.permissions.clone(),
+            version: self.version,
+            owners: self.owners.clone(),
         }
     }
 
+    /// Returns the hash of an entry value, as expected by `UnseqEntryAction::UpdateIfEquals` and
+    /// `UnseqEntryAction::DelIfEquals`.
+    pub fn value_hash(value: &[u8]) -> ValueHash {
+        tiny_keccak::sha3_256(value)
+    }
+
     /// Returns a value for the given key
     pub fn get(&self, key: &[u8]) -> Option<&Vec<u8>> {
         self.data.get(key)
@@ -387,46 +869,101 @@ impl UnseqMutableData {
         &self.data
     }
 
+    /// Returns a page of entries matching the query
+    pub fn entries_page(&self, query: &PageQuery) -> Result<Page<BTreeMap<Vec<u8>, Vec<u8>>>> {
+        let (items, next) = page_of(&self.data, query)?;
+        Ok(Page {
+            items: items
+                .into_iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            next,
+        })
+    }
+
+    /// Returns a page of values matching the query
+    pub fn values_page(&self, query: &PageQuery) -> Result<Page<Vec<Vec<u8>>>> {
+        let (items, next) = page_of(&self.data, query)?;
+        Ok(Page {
+            items: items.into_iter().map(|(_, value)| value.clone()).collect(),
+            next,
+        })
+    }
+
     /// Removes and returns all entries
     pub fn take_entries(&mut self) -> BTreeMap<Vec<u8>, Vec<u8>> {
         mem::replace(&mut self.data, BTreeMap::new())
     }
 
+    /// Mutates entries in bulk, within the default `Limits`
     pub fn mutate_entries(
         &mut self,
         actions: UnseqEntryActions,
         requester: PublicKey,
     ) -> Result<()> {
-        let (insert, update, delete) = actions.actions.into_iter().fold(
-            (
-                BTreeMap::<Vec<u8>, Vec<u8>>::new(),
-                BTreeMap::<Vec<u8>, Vec<u8>>::new(),
-                BTreeSet::<Vec<u8>>::new(),
-            ),
-            |(mut insert, mut update, mut delete), (key, item)| {
-                match item {
-                    UnseqEntryAction::Ins(value) => {
-                        let _ = insert.insert(key, value);
-                    }
-                    UnseqEntryAction::Update(value) => {
-                        let _ = update.insert(key, value);
-                    }
-                    UnseqEntryAction::Del => {
-                        let _ = delete.insert(key);
-                    }
-                };
-                (insert, update, delete)
-            },
-        );
+        self.mutate_entries_cosigned(actions, requester, &BTreeSet::new(), &Limits::default())
+    }
 
-        if *self.owners() != requester
-            && ((!insert.is_empty() && !self.is_action_allowed(&requester, Action::Insert))
-                || (!update.is_empty() && !self.is_action_allowed(&requester, Action::Update))
-                || (!delete.is_empty() && !self.is_action_allowed(&requester, Action::Delete)))
-        {
+    /// Mutates entries in bulk within `limits`, allowing any action if `requester` together with
+    /// `co_signers` satisfy the owners' signing policy.
+    pub fn mutate_entries_cosigned(
+        &mut self,
+        actions: UnseqEntryActions,
+        requester: PublicKey,
+        co_signers: &BTreeSet<PublicKey>,
+        limits: &Limits,
+    ) -> Result<()> {
+        let (insert, update, delete) = actions.into_parts();
+
+        let allowed_for =
+            |action, key: &Vec<u8>| self.is_action_allowed_for_key(&requester, action, key);
+        let allowed = self
+            .owners
+            .is_satisfied_by(iter::once(&requester).chain(co_signers))
+            || (insert.keys().all(|key| allowed_for(Action::Insert, key))
+                && update.keys().all(|key| allowed_for(Action::Update, key))
+                && delete.keys().all(|key| allowed_for(Action::Delete, key)));
+        if !allowed {
             return Err(Error::AccessDenied);
         }
 
+        self.apply_entry_actions(insert, update, delete, limits)
+    }
+
+    /// Computes the entry actions which turn the entries of `self` into those of `other`.
+    pub fn diff(&self, other: &Self) -> UnseqEntryActions {
+        let mut actions = UnseqEntryActions::new();
+        for (key, value) in &other.data {
+            actions = match self.data.get(key) {
+                None => actions.ins(key.clone(), value.clone()),
+                Some(current) if current != value => actions.update(key.clone(), value.clone()),
+                Some(_) => actions,
+            };
+        }
+        for key in self.data.keys() {
+            if !other.data.contains_key(key) {
+                actions = actions.del(key.clone());
+            }
+        }
+        actions
+    }
+
+    /// Applies entry actions computed by `diff`, without checking permissions.
+    ///
+    /// Entries which no longer match the actions (e.g. an insert of an entry which now exists)
+    /// are reported as `Error::InvalidEntryActions`, and nothing is applied in that case.
+    pub fn apply_diff(&mut self, actions: UnseqEntryActions, limits: &Limits) -> Result<()> {
+        let (insert, update, delete) = actions.into_parts();
+        self.apply_entry_actions(insert, update, delete, limits)
+    }
+
+    fn apply_entry_actions(
+        &mut self,
+        insert: BTreeMap<Vec<u8>, Vec<u8>>,
+        update: BTreeMap<Vec<u8>, (Vec<u8>, Option<ValueHash>)>,
+        delete: BTreeMap<Vec<u8>, Option<ValueHash>>,
+        limits: &Limits,
+    ) -> Result<()> {
         let mut new_data = self.data.clone();
         let mut errors = BTreeMap::new();
 
@@ -441,22 +978,32 @@ impl UnseqMutableData {
             }
         }
 
-        for (key, val) in update {
+        for (key, (val, expected)) in update {
             match new_data.entry(key) {
-                Entry::Occupied(mut entry) => {
-                    let _ = entry.insert(val);
-                }
+                Entry::Occupied(mut entry) => match check_value_hash(entry.get(), expected) {
+                    Ok(()) => {
+                        let _ = entry.insert(val);
+                    }
+                    Err(error) => {
+                        let _ = errors.insert(entry.key().clone(), error);
+                    }
+                },
                 Entry::Vacant(entry) => {
                     let _ = errors.insert(entry.key().clone(), EntryError::NoSuchEntry);
                 }
             }
         }
 
-        for key in delete {
-            match new_data.entry(key.clone()) {
-                Entry::Occupied(_) => {
-                    let _ = new_data.remove(&key);
-                }
+        for (key, expected) in delete {
+            match new_data.entry(key) {
+                Entry::Occupied(entry) => match check_value_hash(entry.get(), expected) {
+                    Ok(()) => {
+                        let _ = entry.remove();
+                    }
+                    Err(error) => {
+                        let _ = errors.insert(entry.key().clone(), error);
+                    }
+                },
                 Entry::Vacant(entry) => {
                     let _ = errors.insert(entry.key().clone(), EntryError::NoSuchEntry);
                 }
@@ -467,39 +1014,81 @@ impl UnseqMutableData {
             return Err(Error::InvalidEntryActions(errors));
         }
 
-        let _old_data = mem::replace(&mut self.data, new_data);
+        let old_data = mem::replace(&mut self.data, new_data);
+        if let Err(error) = self.check_limits(limits) {
+            self.data = old_data;
+            return Err(error);
+        }
 
         Ok(())
     }
 }
 
+/// SHA3-256 hash of an unsequenced entry value, used by conditional entry actions.
+pub type ValueHash = [u8; 32];
+
+// Checks the current value of an entry against the hash expected by a conditional action.
+fn check_value_hash(current: &[u8], expected: Option<ValueHash>) -> result::Result<(), EntryError> {
+    match expected {
+        Some(expected) => {
+            let current = UnseqMutableData::value_hash(current);
+            if current == expected {
+                Ok(())
+            } else {
+                Err(EntryError::ValueMismatch(current))
+            }
+        }
+        None => Ok(()),
+    }
+}
+
 /// Implements functions for sequenced Mutable Data.
 impl SeqMutableData {
     /// Create a new Sequenced Mutable Data
-    pub fn new(name: XorName, tag: u64, owners: PublicKey) -> Self {
+    pub fn new(name: XorName, tag: u64, owners: OwnerSet) -> Self {
         Self {
             address: Address::new_seq(name, tag),
             data: Default::default(),
             permissions: Default::default(),
             version: 0,
             owners,
+            history: Default::default(),
+            history_depth: 0,
         }
     }
 
-    /// Create a new Sequenced Mutable Data with entries and permissions
+    /// Create a new Sequenced Mutable Data with entries and permissions, within `limits`
     pub fn new_with_data(
         name: XorName,
         tag: u64,
         data: BTreeMap<Vec<u8>, Value>,
-        permissions: BTreeMap<PublicKey, PermissionSet>,
-        owners: PublicKey,
-    ) -> Self {
-        Self {
+        permissions: BTreeMap<User, PermissionSet>,
+        owners: OwnerSet,
+        limits: &Limits,
+    ) -> Result<Self> {
+        let data = Self {
             address: Address::new_seq(name, tag),
             data,
             permissions,
             version: 0,
             owners,
+            history: Default::default(),
+            history_depth: 0,
+        };
+        data.check_limits(limits)?;
+        Ok(data)
+    }
+
+    /// Returns the Shell of the data
+    pub fn shell(&self) -> Self {
+        Self {
+            address: self.address,
+            data: BTreeMap::new(),
+            permissions: self.permissions.clone(),
+            version: self.version,
+            owners: self.owners.clone(),
+            history: BTreeMap::new(),
+            history_depth: self.history_depth,
         }
     }
 
@@ -508,6 +1097,49 @@ impl SeqMutableData {
         self.data.get(key)
     }
 
+    /// Returns the maximum number of previous values kept per entry.
+    pub fn history_depth(&self) -> u64 {
+        self.history_depth
+    }
+
+    /// Sets the maximum number of previous values kept per entry, discarding the oldest values
+    /// beyond the new depth. A depth of zero disables history.
+    pub fn set_history_depth(&mut self, depth: u64) {
+        self.history_depth = depth;
+        if depth == 0 {
+            self.history.clear();
+        }
+        for values in self.history.values_mut() {
+            truncate_history(values, depth);
+        }
+    }
+
+    /// Returns the value of the given key at the given version, if it is still retained.
+    pub fn get_at_version(&self, key: &[u8], version: u64) -> Option<&Value> {
+        self.data
+            .get(key)
+            .into_iter()
+            .chain(
+                self.history
+                    .get(key)
+                    .into_iter()
+                    .flat_map(|values| values.iter().rev()),
+            )
+            .find(|value| value.version == version)
+    }
+
+    /// Returns the retained values of the given key, oldest first and ending with the current
+    /// value if the entry hasn't been deleted.
+    pub fn value_history(&self, key: &[u8]) -> Vec<Value> {
+        self.history
+            .get(key)
+            .into_iter()
+            .flat_map(|values| values.iter())
+            .chain(self.data.get(key))
+            .cloned()
+            .collect()
+    }
+
     /// Returns values of all entries
     pub fn values(&self) -> Vec<Value> {
         self.data.values().cloned().collect()
@@ -518,42 +1150,118 @@ impl SeqMutableData {
         &self.data
     }
 
+    /// Returns a page of entries matching the query
+    pub fn entries_page(&self, query: &PageQuery) -> Result<Page<BTreeMap<Vec<u8>, Value>>> {
+        let (items, next) = page_of(&self.data, query)?;
+        Ok(Page {
+            items: items
+                .into_iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            next,
+        })
+    }
+
+    /// Returns a page of values matching the query
+    pub fn values_page(&self, query: &PageQuery) -> Result<Page<Vec<Value>>> {
+        let (items, next) = page_of(&self.data, query)?;
+        Ok(Page {
+            items: items.into_iter().map(|(_, value)| value.clone()).collect(),
+            next,
+        })
+    }
+
     /// Removes and returns all entries
     pub fn take_entries(&mut self) -> BTreeMap<Vec<u8>, Value> {
         mem::replace(&mut self.data, BTreeMap::new())
     }
 
-    /// Mutates entries (key + value pairs) in bulk
+    /// Mutates entries (key + value pairs) in bulk, within the default `Limits`
     pub fn mutate_entries(&mut self, actions: SeqEntryActions, requester: PublicKey) -> Result<()> {
-        // Deconstruct actions into inserts, updates, and deletes
-        let (insert, update, delete) = actions.actions.into_iter().fold(
-            (BTreeMap::new(), BTreeMap::new(), BTreeMap::new()),
-            |(mut insert, mut update, mut delete), (key, item)| {
-                match item {
-                    SeqEntryAction::Ins(value) => {
-                        let _ = insert.insert(key, value);
-                    }
-                    SeqEntryAction::Update(value) => {
-                        let _ = update.insert(key, value);
-                    }
-                    SeqEntryAction::Del(version) => {
-                        let _ = delete.insert(key, version);
-                    }
-                };
-                (insert, update, delete)
-            },
-        );
+        self.mutate_entries_cosigned(actions, requester, &BTreeSet::new(), &Limits::default())
+    }
 
-        if *self.owners() != requester
-            && ((!insert.is_empty() && !self.is_action_allowed(&requester, Action::Insert))
-                || (!update.is_empty() && !self.is_action_allowed(&requester, Action::Update))
-                || (!delete.is_empty() && !self.is_action_allowed(&requester, Action::Delete)))
-        {
+    /// Mutates entries in bulk within `limits`, allowing any action if `requester` together with
+    /// `co_signers` satisfy the owners' signing policy.
+    pub fn mutate_entries_cosigned(
+        &mut self,
+        actions: SeqEntryActions,
+        requester: PublicKey,
+        co_signers: &BTreeSet<PublicKey>,
+        limits: &Limits,
+    ) -> Result<()> {
+        // Deconstruct actions into inserts, updates, and deletes
+        let (insert, update, delete) = actions.into_parts();
+
+        let allowed_for =
+            |action, key: &Vec<u8>| self.is_action_allowed_for_key(&requester, action, key);
+        let allowed = self
+            .owners
+            .is_satisfied_by(iter::once(&requester).chain(co_signers))
+            || (insert.keys().all(|key| allowed_for(Action::Insert, key))
+                && update.keys().all(|key| allowed_for(Action::Update, key))
+                && delete.keys().all(|key| allowed_for(Action::Delete, key)));
+        if !allowed {
             return Err(Error::AccessDenied);
         }
 
+        self.apply_entry_actions(insert, update, delete, limits, |current, version| {
+            version == current + 1
+        })
+    }
+
+    /// Computes the entry actions which turn the entries of `self` into those of `other`.
+    ///
+    /// Inserted and updated entries carry their value and version from `other`, while deleted
+    /// entries are given the version following their current one. As updates may skip versions,
+    /// the result is to be applied with `apply_diff` rather than `mutate_entries`.
+    pub fn diff(&self, other: &Self) -> SeqEntryActions {
+        let mut actions = SeqEntryActions::new();
+        for (key, value) in &other.data {
+            actions = match self.data.get(key) {
+                None => actions.ins(key.clone(), value.data.clone(), value.version),
+                Some(current) if current != value => {
+                    actions.update(key.clone(), value.data.clone(), value.version)
+                }
+                Some(_) => actions,
+            };
+        }
+        for (key, value) in &self.data {
+            if !other.data.contains_key(key) {
+                actions = actions.del(key.clone(), value.version + 1);
+            }
+        }
+        actions
+    }
+
+    /// Applies entry actions computed by `diff`, without checking permissions.
+    ///
+    /// Unlike with `mutate_entries`, updates and deletions may skip versions, but must still move
+    /// each entry to a later version. Entries which conflict with the actions (e.g. were updated
+    /// past the given version in the meantime) are reported as `Error::InvalidEntryActions`, and
+    /// nothing is applied in that case.
+    pub fn apply_diff(&mut self, actions: SeqEntryActions, limits: &Limits) -> Result<()> {
+        let (insert, update, delete) = actions.into_parts();
+        self.apply_entry_actions(insert, update, delete, limits, |current, version| {
+            version > current
+        })
+    }
+
+    // Applies the actions, accepting a new version of an entry if `is_successor(current, new)`.
+    fn apply_entry_actions<F>(
+        &mut self,
+        insert: BTreeMap<Vec<u8>, Value>,
+        update: BTreeMap<Vec<u8>, Value>,
+        delete: BTreeMap<Vec<u8>, u64>,
+        limits: &Limits,
+        is_successor: F,
+    ) -> Result<()>
+    where
+        F: Fn(u64, u64) -> bool,
+    {
         let mut new_data = self.data.clone();
         let mut errors = BTreeMap::new();
+        let mut replaced = Vec::new();
 
         for (key, val) in insert {
             match new_data.entry(key) {
@@ -573,8 +1281,9 @@ impl SeqMutableData {
             match new_data.entry(key) {
                 Entry::Occupied(mut entry) => {
                     let current_version = entry.get().version;
-                    if val.version == current_version + 1 {
-                        let _ = entry.insert(val);
+                    if is_successor(current_version, val.version) {
+                        let old_value = entry.insert(val);
+                        replaced.push((entry.key().clone(), old_value));
                     } else {
                         let _ = errors.insert(
                             entry.key().clone(),
@@ -592,8 +1301,8 @@ impl SeqMutableData {
             match new_data.entry(key.clone()) {
                 Entry::Occupied(entry) => {
                     let current_version = entry.get().version;
-                    if version == current_version + 1 {
-                        let _ = new_data.remove(&key);
+                    if is_successor(current_version, version) {
+                        replaced.push((key, entry.remove()));
                     } else {
                         let _ = errors.insert(
                             entry.key().clone(),
@@ -611,12 +1320,92 @@ impl SeqMutableData {
             return Err(Error::InvalidEntryActions(errors));
         }
 
-        let _old_data = mem::replace(&mut self.data, new_data);
+        // The history counts towards the size of the data, so it is updated before the limits are
+        // checked.
+        let old_data = mem::replace(&mut self.data, new_data);
+        let old_history = if self.history_depth > 0 && !replaced.is_empty() {
+            let old_history = self.history.clone();
+            for (key, value) in replaced {
+                let values = self.history.entry(key).or_default();
+                values.push(value);
+                truncate_history(values, self.history_depth);
+            }
+            Some(old_history)
+        } else {
+            None
+        };
+        if let Err(error) = self.check_limits(limits) {
+            self.data = old_data;
+            if let Some(old_history) = old_history {
+                self.history = old_history;
+            }
+            return Err(error);
+        }
 
         Ok(())
     }
 }
 
+impl Verifiable for SeqMutableData {
+    type Address = Address;
+
+    /// Checks the kind, name and tag of the data, its owners and the retained history of its
+    /// entries.
+    fn verify(&self, address: &Address) -> Result<()> {
+        if !self.address.is_seq() {
+            return Err(Error::InvalidData(VerificationError::KindMismatch));
+        }
+        verify_common(&self.address, &self.owners, address)?;
+        match self
+            .history
+            .iter()
+            .find(|(_, values)| values.len() as u64 > self.history_depth)
+        {
+            Some((key, _)) => Err(Error::InvalidData(VerificationError::InvalidHistory(
+                key.clone(),
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Verifiable for UnseqMutableData {
+    type Address = Address;
+
+    /// Checks the kind, name and tag of the data, and its owners.
+    fn verify(&self, address: &Address) -> Result<()> {
+        if !self.address.is_unseq() {
+            return Err(Error::InvalidData(VerificationError::KindMismatch));
+        }
+        verify_common(&self.address, &self.owners, address)
+    }
+}
+
+// Checks `actual` against the `expected` address, and that `owners` have a signing policy they can
+// satisfy.
+fn verify_common(actual: &Address, owners: &OwnerSet, expected: &Address) -> Result<()> {
+    if actual.is_seq() != expected.is_seq() {
+        return Err(Error::InvalidData(VerificationError::KindMismatch));
+    }
+    if actual.name() != expected.name() {
+        return Err(Error::InvalidData(VerificationError::NameMismatch));
+    }
+    if actual.tag() != expected.tag() {
+        return Err(Error::InvalidData(VerificationError::TagMismatch));
+    }
+    owners
+        .validate()
+        .map_err(|_| Error::InvalidData(VerificationError::InvalidOwners))
+}
+
+// Drops the oldest values so that at most `depth` remain.
+fn truncate_history(values: &mut Vec<Value>, depth: u64) {
+    let len = values.len() as u64;
+    if len > depth {
+        let _ = values.drain(..(len - depth) as usize);
+    }
+}
+
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
 pub enum Address {
     Unseq { name: XorName, tag: u64 },
@@ -687,6 +1476,10 @@ pub enum UnseqEntryAction {
     Update(Vec<u8>),
     /// Deletes an entry
     Del,
+    /// Updates an entry with a new value if the hash of its current value equals the given one
+    UpdateIfEquals(ValueHash, Vec<u8>),
+    /// Deletes an entry if the hash of its current value equals the given one
+    DelIfEquals(ValueHash),
 }
 
 /// Helper struct to build entry actions on `MutableData`
@@ -730,6 +1523,39 @@ impl SeqEntryActions {
         let _ = self.actions.insert(key, SeqEntryAction::Del(version));
         self
     }
+
+    /// Returns true if there are no actions
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    // Splits the actions into inserts, updates and deletes.
+    #[allow(clippy::type_complexity)]
+    fn into_parts(
+        self,
+    ) -> (
+        BTreeMap<Vec<u8>, Value>,
+        BTreeMap<Vec<u8>, Value>,
+        BTreeMap<Vec<u8>, u64>,
+    ) {
+        self.actions.into_iter().fold(
+            (BTreeMap::new(), BTreeMap::new(), BTreeMap::new()),
+            |(mut insert, mut update, mut delete), (key, item)| {
+                match item {
+                    SeqEntryAction::Ins(value) => {
+                        let _ = insert.insert(key, value);
+                    }
+                    SeqEntryAction::Update(value) => {
+                        let _ = update.insert(key, value);
+                    }
+                    SeqEntryAction::Del(version) => {
+                        let _ = delete.insert(key, version);
+                    }
+                };
+                (insert, update, delete)
+            },
+        )
+    }
 }
 
 impl Into<BTreeMap<Vec<u8>, SeqEntryAction>> for SeqEntryActions {
@@ -766,6 +1592,62 @@ impl UnseqEntryActions {
         let _ = self.actions.insert(key, UnseqEntryAction::Del);
         self
     }
+
+    /// Update existing key-value pair if the hash of its current value equals `expected`
+    pub fn update_if_equals(mut self, key: Vec<u8>, expected: ValueHash, content: Vec<u8>) -> Self {
+        let _ = self
+            .actions
+            .insert(key, UnseqEntryAction::UpdateIfEquals(expected, content));
+        self
+    }
+
+    /// Delete existing key if the hash of its current value equals `expected`
+    pub fn del_if_equals(mut self, key: Vec<u8>, expected: ValueHash) -> Self {
+        let _ = self
+            .actions
+            .insert(key, UnseqEntryAction::DelIfEquals(expected));
+        self
+    }
+
+    /// Returns true if there are no actions
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    // Splits the actions into inserts, updates and deletes, with the value hashes expected by
+    // conditional updates and deletes.
+    #[allow(clippy::type_complexity)]
+    fn into_parts(
+        self,
+    ) -> (
+        BTreeMap<Vec<u8>, Vec<u8>>,
+        BTreeMap<Vec<u8>, (Vec<u8>, Option<ValueHash>)>,
+        BTreeMap<Vec<u8>, Option<ValueHash>>,
+    ) {
+        self.actions.into_iter().fold(
+            (BTreeMap::new(), BTreeMap::new(), BTreeMap::new()),
+            |(mut insert, mut update, mut delete), (key, item)| {
+                match item {
+                    UnseqEntryAction::Ins(value) => {
+                        let _ = insert.insert(key, value);
+                    }
+                    UnseqEntryAction::Update(value) => {
+                        let _ = update.insert(key, (value, None));
+                    }
+                    UnseqEntryAction::UpdateIfEquals(expected, value) => {
+                        let _ = update.insert(key, (value, Some(expected)));
+                    }
+                    UnseqEntryAction::Del => {
+                        let _ = delete.insert(key, None);
+                    }
+                    UnseqEntryAction::DelIfEquals(expected) => {
+                        let _ = delete.insert(key, Some(expected));
+                    }
+                };
+                (insert, update, delete)
+            },
+        )
+    }
 }
 
 impl Into<BTreeMap<Vec<u8>, UnseqEntryAction>> for UnseqEntryActions {
@@ -774,10 +1656,52 @@ impl Into<BTreeMap<Vec<u8>, UnseqEntryAction>> for UnseqEntryActions {
     }
 }
 
+/// A change applied to a `MutableData`.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum Change {
+    /// Entry actions applied to a sequenced Mutable Data.
+    SeqEntries(SeqEntryActions),
+    /// Entry actions applied to an unsequenced Mutable Data.
+    UnseqEntries(UnseqEntryActions),
+    /// Permissions of `user` were inserted or updated.
+    SetUserPermissions {
+        user: User,
+        permissions: PermissionSet,
+    },
+    /// Permissions of `user` were removed.
+    DelUserPermissions { user: User },
+    /// The data was transferred to `owners`.
+    SetOwners { owners: OwnerSet },
+}
+
+/// A change to a `MutableData`, as delivered to its subscribers.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub struct Event {
+    /// Address of the Mutable Data which changed.
+    pub address: Address,
+    /// The change, as it was applied.
+    pub change: Change,
+    /// Version of the Mutable Data after the change.
+    pub version: u64,
+}
+
 #[cfg(test)]
 mod test {
-    use super::{Address, XorName};
+    use super::{
+        Action, Address, Denial, EntryError, Error, Limits, MutableData, OwnerSet, PageQuery,
+        PermissionSet, SeqEntryActions, SeqMutableData, UnseqEntryActions, UnseqMutableData, User,
+        XorName, MAX_MUTABLE_DATA_PAGE_SIZE,
+    };
+    use crate::{
+        ClientFullId, Request, Verifiable, VerificationError, MAX_DATA_SIZE_IN_BYTES,
+        MAX_ENTRY_KEY_SIZE_IN_BYTES, MAX_ENTRY_VALUE_SIZE_IN_BYTES, MAX_MUTABLE_DATA_ENTRIES,
+    };
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        iter,
+    };
     use unwrap::unwrap;
+
     #[test]
     fn zbase32_encode_decode_mdata_address() {
         let name = XorName(rand::random());
@@ -786,4 +1710,647 @@ mod test {
         let decoded = unwrap!(self::Address::decode_from_zbase32(&encoded));
         assert_eq!(address, decoded);
     }
+
+    #[test]
+    fn value_history() {
+        let owner = *ClientFullId::new_ed25519(&mut rand::thread_rng())
+            .public_id()
+            .public_key();
+        let mut data = SeqMutableData::new(rand::random(), 100, owner.into());
+        data.set_history_depth(2);
+
+        let key = b"config".to_vec();
+        let actions = SeqEntryActions::new().ins(key.clone(), b"v0".to_vec(), 0);
+        unwrap!(data.mutate_entries(actions, owner));
+        for version in 1..4 {
+            let content = format!("v{}", version).into_bytes();
+            let actions = SeqEntryActions::new().update(key.clone(), content, version);
+            unwrap!(data.mutate_entries(actions, owner));
+        }
+
+        // Only the last two previous values are retained.
+        let versions: Vec<_> = data
+            .value_history(&key)
+            .into_iter()
+            .map(|value| value.version)
+            .collect();
+        assert_eq!(versions, vec![1, 2, 3]);
+        assert!(data.get_at_version(&key, 0).is_none());
+        assert_eq!(unwrap!(data.get_at_version(&key, 1)).data, b"v1".to_vec());
+
+        // Deleted values stay in the history.
+        let actions = SeqEntryActions::new().del(key.clone(), 4);
+        unwrap!(data.mutate_entries(actions, owner));
+        assert!(data.get(&key).is_none());
+        assert_eq!(unwrap!(data.get_at_version(&key, 3)).data, b"v3".to_vec());
+        assert_eq!(data.value_history(&key).len(), 2);
+        assert!(data.shell().value_history(&key).is_empty());
+
+        data.set_history_depth(0);
+        assert!(data.value_history(&key).is_empty());
+    }
+
+    #[test]
+    fn verify() {
+        let owner = *ClientFullId::new_ed25519(&mut rand::thread_rng())
+            .public_id()
+            .public_key();
+        let name = rand::random();
+        let mut data = SeqMutableData::new(name, 100, owner.into());
+        data.set_history_depth(1);
+        let actions = SeqEntryActions::new().ins(b"key".to_vec(), b"v0".to_vec(), 0);
+        unwrap!(data.mutate_entries(actions, owner));
+        let actions = SeqEntryActions::new().update(b"key".to_vec(), b"v1".to_vec(), 1);
+        unwrap!(data.mutate_entries(actions, owner));
+        unwrap!(data.verify(&Address::new_seq(name, 100)));
+
+        let invalid = |error| Err(Error::InvalidData(error));
+        assert_eq!(
+            data.verify(&Address::new_unseq(name, 100)),
+            invalid(VerificationError::KindMismatch)
+        );
+        assert_eq!(
+            data.verify(&Address::new_seq(rand::random(), 100)),
+            invalid(VerificationError::NameMismatch)
+        );
+        assert_eq!(
+            data.verify(&Address::new_seq(name, 101)),
+            invalid(VerificationError::TagMismatch)
+        );
+
+        // History beyond the history depth.
+        let mut tampered = data.clone();
+        tampered.history_depth = 0;
+        assert_eq!(
+            tampered.verify(&Address::new_seq(name, 100)),
+            invalid(VerificationError::InvalidHistory(b"key".to_vec()))
+        );
+
+        // Data of the other flavour at the same address.
+        let mut tampered = UnseqMutableData::new(name, 100, owner.into());
+        tampered.address = Address::new_seq(name, 100);
+        assert_eq!(
+            tampered.verify(&Address::new_seq(name, 100)),
+            invalid(VerificationError::KindMismatch)
+        );
+    }
+
+    #[test]
+    fn conditional_actions() {
+        let owner = *ClientFullId::new_ed25519(&mut rand::thread_rng())
+            .public_id()
+            .public_key();
+        let mut data = UnseqMutableData::new(rand::random(), 100, owner.into());
+        let key = b"lock".to_vec();
+        let actions = UnseqEntryActions::new().ins(key.clone(), b"free".to_vec());
+        unwrap!(data.mutate_entries(actions, owner));
+
+        let free = UnseqMutableData::value_hash(b"free");
+        let taken = UnseqMutableData::value_hash(b"taken");
+        let actions =
+            UnseqEntryActions::new().update_if_equals(key.clone(), free, b"taken".to_vec());
+        unwrap!(data.mutate_entries(actions.clone(), owner));
+
+        // A second writer with a stale view fails and learns the current hash.
+        match data.mutate_entries(actions, owner) {
+            Err(Error::InvalidEntryActions(errors)) => {
+                assert_eq!(errors.get(&key), Some(&EntryError::ValueMismatch(taken)))
+            }
+            x => panic!("Unexpected result: {:?}", x),
+        }
+        assert_eq!(data.get(&key), Some(&b"taken".to_vec()));
+
+        let actions = UnseqEntryActions::new().del_if_equals(key.clone(), free);
+        match data.mutate_entries(actions, owner) {
+            Err(Error::InvalidEntryActions(errors)) => {
+                assert_eq!(errors.get(&key), Some(&EntryError::ValueMismatch(taken)))
+            }
+            x => panic!("Unexpected result: {:?}", x),
+        }
+        let actions = UnseqEntryActions::new().del_if_equals(key.clone(), taken);
+        unwrap!(data.mutate_entries(actions, owner));
+        assert!(data.get(&key).is_none());
+
+        let actions = UnseqEntryActions::new().del_if_equals(key.clone(), taken);
+        match data.mutate_entries(actions, owner) {
+            Err(Error::InvalidEntryActions(errors)) => {
+                assert_eq!(errors.get(&key), Some(&EntryError::NoSuchEntry))
+            }
+            x => panic!("Unexpected result: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn diff_and_apply() {
+        let owner = *ClientFullId::new_ed25519(&mut rand::thread_rng())
+            .public_id()
+            .public_key();
+        let user = User::Key(
+            *ClientFullId::new_ed25519(&mut rand::thread_rng())
+                .public_id()
+                .public_key(),
+        );
+
+        let mut data = SeqMutableData::new(rand::random(), 100, owner.into());
+        let actions = SeqEntryActions::new()
+            .ins(b"a".to_vec(), b"a0".to_vec(), 0)
+            .ins(b"c".to_vec(), b"c0".to_vec(), 0);
+        unwrap!(data.mutate_entries(actions, owner));
+        let mut cache = data.clone();
+        assert!(cache.diff(&data).is_empty());
+
+        // The cache misses several changes, including two updates of the same entry.
+        let actions = SeqEntryActions::new()
+            .ins(b"b".to_vec(), b"b0".to_vec(), 0)
+            .update(b"a".to_vec(), b"a1".to_vec(), 1)
+            .del(b"c".to_vec(), 1);
+        unwrap!(data.mutate_entries(actions, owner));
+        let actions = SeqEntryActions::new().update(b"a".to_vec(), b"a2".to_vec(), 2);
+        unwrap!(data.mutate_entries(actions, owner));
+        unwrap!(data.set_user_permissions(
+            user,
+            PermissionSet::new().allow(Action::Read),
+            1,
+            &Limits::default()
+        ));
+        unwrap!(data.set_user_permissions(
+            user,
+            PermissionSet::new().allow(Action::Insert),
+            2,
+            &Limits::default()
+        ));
+
+        let actions = cache.diff(&data);
+        unwrap!(cache.apply_diff(actions.clone(), &Limits::default()));
+        assert_eq!(cache.entries(), data.entries());
+        match cache.apply_diff(actions, &Limits::default()) {
+            Err(Error::InvalidEntryActions(errors)) => {
+                assert_eq!(
+                    errors.get(&b"a"[..]),
+                    Some(&EntryError::InvalidSuccessor(2))
+                );
+                assert_eq!(errors.get(&b"b"[..]), Some(&EntryError::EntryExists(0)));
+                assert_eq!(errors.get(&b"c"[..]), Some(&EntryError::NoSuchEntry));
+            }
+            x => panic!("Unexpected result: {:?}", x),
+        }
+
+        let diff = cache.permissions_diff(&data);
+        assert_eq!(diff.set.len(), 1);
+        assert_eq!(diff.version, 2);
+        unwrap!(cache.apply_permissions_diff(diff.clone()));
+        assert_eq!(cache.permissions(), data.permissions());
+        assert_eq!(cache.version(), 2);
+        match cache.apply_permissions_diff(diff) {
+            Err(Error::InvalidSuccessor(2)) => (),
+            x => panic!("Unexpected result: {:?}", x),
+        }
+
+        unwrap!(data.del_user_permissions(user, 3));
+        let diff = cache.permissions_diff(&data);
+        assert!(diff.set.is_empty());
+        assert!(diff.deleted.contains(&user));
+        unwrap!(cache.apply_permissions_diff(diff));
+        assert!(cache.permissions().is_empty());
+
+        let mut data = UnseqMutableData::new(rand::random(), 100, owner.into());
+        let actions = UnseqEntryActions::new()
+            .ins(b"a".to_vec(), b"a0".to_vec())
+            .ins(b"c".to_vec(), b"c0".to_vec());
+        unwrap!(data.mutate_entries(actions, owner));
+        let mut cache = data.clone();
+        let actions = UnseqEntryActions::new()
+            .ins(b"b".to_vec(), b"b0".to_vec())
+            .update(b"a".to_vec(), b"a1".to_vec())
+            .del(b"c".to_vec());
+        unwrap!(data.mutate_entries(actions, owner));
+
+        let actions = cache.diff(&data);
+        unwrap!(cache.apply_diff(actions.clone(), &Limits::default()));
+        assert_eq!(cache.entries(), data.entries());
+        assert!(cache.diff(&data).is_empty());
+        match cache.apply_diff(actions, &Limits::default()) {
+            Err(Error::InvalidEntryActions(errors)) => {
+                assert_eq!(errors.get(&b"b"[..]), Some(&EntryError::EntryExists(0)));
+                assert_eq!(errors.get(&b"c"[..]), Some(&EntryError::NoSuchEntry));
+            }
+            x => panic!("Unexpected result: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn pagination() {
+        let owner = *ClientFullId::new_ed25519(&mut rand::thread_rng())
+            .public_id()
+            .public_key();
+        let entries: BTreeMap<_, _> = ["a1", "a2", "a3", "b1", "b2"]
+            .iter()
+            .map(|key| (key.as_bytes().to_vec(), key.as_bytes().to_vec()))
+            .collect();
+        let data = unwrap!(UnseqMutableData::new_with_data(
+            rand::random(),
+            100,
+            entries.clone(),
+            BTreeMap::new(),
+            owner.into(),
+            &Limits::default(),
+        ));
+
+        // Collect all entries two at a time.
+        let mut query = PageQuery::new(2);
+        let mut collected = BTreeMap::new();
+        let mut pages = 0;
+        loop {
+            let page = unwrap!(data.entries_page(&query));
+            assert!(page.items.len() <= 2);
+            collected.extend(page.items);
+            pages += 1;
+            match page.next {
+                Some(next) => query = query.starting_at(next),
+                None => break,
+            }
+        }
+        assert_eq!(collected, entries);
+        assert_eq!(pages, 3);
+
+        let page = unwrap!(data.keys_page(&PageQuery::new(2).with_prefix(b"a".to_vec())));
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.next, Some(b"a3".to_vec()));
+        let page = unwrap!(data.values_page(
+            &PageQuery::new(2)
+                .with_prefix(b"a".to_vec())
+                .starting_at(b"a3".to_vec()),
+        ));
+        assert_eq!(page.items, vec![b"a3".to_vec()]);
+        assert_eq!(page.next, None);
+
+        // A start key before the prefix starts at the prefix.
+        let page = unwrap!(data.keys_page(
+            &PageQuery::new(10)
+                .with_prefix(b"b".to_vec())
+                .starting_at(b"a".to_vec()),
+        ));
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.next, None);
+
+        // An empty page would never advance.
+        assert_eq!(
+            data.keys_page(&PageQuery::new(0)),
+            Err(Error::InvalidOperation)
+        );
+
+        // Pages are capped at the maximum page size.
+        let entries: BTreeMap<_, _> = (0..MAX_MUTABLE_DATA_PAGE_SIZE + 1)
+            .map(|i| (i.to_be_bytes().to_vec(), vec![]))
+            .collect();
+        let data = unwrap!(UnseqMutableData::new_with_data(
+            rand::random(),
+            100,
+            entries.clone(),
+            BTreeMap::new(),
+            owner.into(),
+            &Limits::default(),
+        ));
+        let page = unwrap!(data.entries_page(&PageQuery::new(MAX_MUTABLE_DATA_PAGE_SIZE + 1)));
+        assert_eq!(page.items.len() as u64, MAX_MUTABLE_DATA_PAGE_SIZE);
+        assert_eq!(
+            page.next,
+            Some(MAX_MUTABLE_DATA_PAGE_SIZE.to_be_bytes().to_vec())
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let owner = *ClientFullId::new_ed25519(&mut rand::thread_rng())
+            .public_id()
+            .public_key();
+
+        let too_many: BTreeMap<_, _> = (0..=MAX_MUTABLE_DATA_ENTRIES)
+            .map(|i| (i.to_string().into_bytes(), Vec::new()))
+            .collect();
+        match UnseqMutableData::new_with_data(
+            rand::random(),
+            100,
+            too_many,
+            BTreeMap::new(),
+            owner.into(),
+            &Limits::default(),
+        ) {
+            Err(Error::TooManyEntries) => (),
+            x => panic!("Unexpected result: {:?}", x.map(|_| ())),
+        }
+
+        let mut data = SeqMutableData::new(rand::random(), 100, owner.into());
+        let big_key = vec![0; MAX_ENTRY_KEY_SIZE_IN_BYTES as usize + 1];
+        let actions = SeqEntryActions::new().ins(big_key, Vec::new(), 0);
+        assert_eq!(
+            data.mutate_entries(actions, owner),
+            Err(Error::ExceededSize)
+        );
+
+        // Values within the per-entry limit, but exceeding the total size together.
+        let value = vec![0; MAX_ENTRY_VALUE_SIZE_IN_BYTES as usize];
+        let count = MAX_DATA_SIZE_IN_BYTES / MAX_ENTRY_VALUE_SIZE_IN_BYTES;
+        let actions = (0..count).fold(SeqEntryActions::new(), |actions, i| {
+            actions.ins(i.to_string().into_bytes(), value.clone(), 0)
+        });
+        unwrap!(data.mutate_entries(actions, owner));
+        let actions = SeqEntryActions::new().ins(b"last".to_vec(), value, 0);
+        assert_eq!(
+            data.mutate_entries(actions, owner),
+            Err(Error::ExceededSize)
+        );
+        assert_eq!(data.keys().len() as u64, count);
+
+        // Permissions count towards the total size too.
+        let user = User::Key(
+            *ClientFullId::new_ed25519(&mut rand::thread_rng())
+                .public_id()
+                .public_key(),
+        );
+        let mut limits = Limits::default();
+        unwrap!(data.check_limits(&limits));
+        limits.max_size = data.serialised_size();
+        unwrap!(data.check_limits(&limits));
+        assert_eq!(
+            data.set_user_permissions(user, PermissionSet::new(), 1, &limits),
+            Err(Error::ExceededSize)
+        );
+        assert!(data.permissions().is_empty());
+        assert_eq!(data.version(), 0);
+        limits.max_size -= 1;
+        assert_eq!(data.check_limits(&limits), Err(Error::ExceededSize));
+        unwrap!(data.set_user_permissions(user, PermissionSet::new(), 1, &Limits::default()));
+
+        // Mutations are checked against the limits they are given.
+        let limits = Limits {
+            max_entries: count,
+            ..Limits::default()
+        };
+        let actions = SeqEntryActions::new().del(b"0".to_vec(), 1);
+        unwrap!(data.mutate_entries_cosigned(actions, owner, &BTreeSet::new(), &limits));
+        let actions = SeqEntryActions::new()
+            .ins(b"0".to_vec(), Vec::new(), 0)
+            .ins(b"extra".to_vec(), Vec::new(), 0);
+        assert_eq!(
+            data.mutate_entries_cosigned(actions, owner, &BTreeSet::new(), &limits),
+            Err(Error::TooManyEntries)
+        );
+
+        // The retained history counts towards the total size too.
+        let mut data = SeqMutableData::new(rand::random(), 100, owner.into());
+        data.set_history_depth(1);
+        let value = vec![0; 1000];
+        let actions = SeqEntryActions::new().ins(b"key".to_vec(), value.clone(), 0);
+        unwrap!(data.mutate_entries(actions, owner));
+        let limits = Limits {
+            max_size: data.serialised_size() + 10,
+            ..Limits::default()
+        };
+        let actions = SeqEntryActions::new().update(b"key".to_vec(), Vec::new(), 1);
+        assert_eq!(
+            data.mutate_entries_cosigned(actions.clone(), owner, &BTreeSet::new(), &limits),
+            Err(Error::ExceededSize)
+        );
+        assert_eq!(unwrap!(data.get(b"key")).data, value);
+        assert_eq!(data.value_history(b"key").len(), 1);
+        unwrap!(data.check_limits(&limits));
+        unwrap!(data.mutate_entries(actions, owner));
+        assert_eq!(unwrap!(data.get_at_version(b"key", 0)).data, value);
+    }
+
+    #[test]
+    fn multi_owner() {
+        let mut rng = rand::thread_rng();
+        let ids: Vec<_> = (0..3)
+            .map(|_| ClientFullId::new_ed25519(&mut rng))
+            .collect();
+        let keys: Vec<_> = ids.iter().map(|id| *id.public_id().public_key()).collect();
+        let owners = unwrap!(OwnerSet::new(keys.iter().cloned().collect(), 2));
+        let mut data = SeqMutableData::new(rand::random(), 100, owners);
+
+        // A single owner doesn't satisfy the threshold on its own.
+        let address = *data.address();
+        assert_eq!(
+            data.check_permissions(Request::DeleteMData(address), keys[0]),
+            Err(Error::AccessDenied)
+        );
+        let actions = SeqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec(), 0);
+        assert_eq!(
+            data.mutate_entries(actions, keys[0]),
+            Err(Error::AccessDenied)
+        );
+
+        // Approved by a second owner.
+        let co_signed = Request::CoSigned {
+            request: Box::new(Request::DeleteMData(address)),
+            signatures: iter::once((keys[1], ids[1].sign(b"verified by the vault"))).collect(),
+        };
+        unwrap!(data.check_permissions(co_signed, keys[0]));
+        let actions = SeqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec(), 0);
+        let co_signers = iter::once(keys[1]).collect();
+        unwrap!(data.mutate_entries_cosigned(actions, keys[0], &co_signers, &Limits::default()));
+
+        // Ownership transfer approved by two of the three owners.
+        let new_owners = OwnerSet::from(keys[2]);
+        let payload = b"change owners";
+        let signatures: BTreeMap<_, _> = ids[..2]
+            .iter()
+            .map(|id| (*id.public_id().public_key(), id.sign(payload)))
+            .collect();
+        unwrap!(data.check_owner_signatures(payload, &signatures));
+        assert_eq!(
+            data.check_owner_signatures(payload, &BTreeMap::new()),
+            Err(Error::InvalidOwners)
+        );
+        unwrap!(data.change_owner(new_owners.clone(), 1));
+        assert_eq!(*data.owners(), new_owners);
+        unwrap!(data.check_permissions(Request::DeleteMData(address), keys[2]));
+    }
+
+    #[test]
+    fn scoped_permissions() {
+        let mut rng = rand::thread_rng();
+        let owner = *ClientFullId::new_ed25519(&mut rng).public_id().public_key();
+        let app = *ClientFullId::new_ed25519(&mut rng).public_id().public_key();
+        let stranger = *ClientFullId::new_ed25519(&mut rng).public_id().public_key();
+
+        let mut data = UnseqMutableData::new(rand::random(), 100, owner.into());
+        let address = *data.address();
+        unwrap!(data.set_user_permissions(
+            User::Anyone,
+            PermissionSet::new()
+                .allow(Action::Read)
+                .allow_for_prefix(Action::Insert, b"public/".to_vec()),
+            1,
+            &Limits::default()
+        ));
+        unwrap!(data.set_user_permissions(
+            User::Key(app),
+            PermissionSet::new()
+                .allow_for_prefix(Action::Insert, b"inbox/".to_vec())
+                .deny_for_prefix(Action::Insert, b"inbox/sealed/".to_vec())
+                .deny(Action::Read),
+            2,
+            &Limits::default()
+        ));
+
+        // Anyone can read, except the app which is denied it explicitly.
+        unwrap!(data.check_permissions(Request::GetMData(address), stranger));
+        assert_eq!(
+            data.check_permissions(Request::GetMData(address), app),
+            Err(Error::AccessDenied)
+        );
+
+        // The app can only insert under its prefix, minus the denied sub-prefix.
+        let insert = |key: &[u8]| UnseqEntryActions::new().ins(key.to_vec(), b"value".to_vec());
+        unwrap!(data.mutate_entries(insert(b"inbox/a"), app));
+        assert_eq!(
+            data.mutate_entries(insert(b"inbox/sealed/a"), app),
+            Err(Error::AccessDenied)
+        );
+        assert_eq!(
+            data.mutate_entries(insert(b"other"), app),
+            Err(Error::AccessDenied)
+        );
+        // Nothing is applied if any key is out of scope.
+        let actions = insert(b"inbox/b").ins(b"other".to_vec(), b"value".to_vec());
+        assert_eq!(data.mutate_entries(actions, app), Err(Error::AccessDenied));
+        assert!(data.get(b"inbox/b").is_none());
+
+        // Actions which the app's own permissions don't mention fall back to `User::Anyone`.
+        unwrap!(data.mutate_entries(insert(b"public/a"), stranger));
+        unwrap!(data.mutate_entries(insert(b"public/b"), app));
+
+        // Scoped permissions don't grant the action on the whole data.
+        assert!(data.is_action_allowed_for_key(&app, Action::Insert, b"inbox/c"));
+        assert!(!data.is_action_allowed(&app, Action::Insert));
+        assert!(!data.is_action_allowed(&stranger, Action::Update));
+
+        // Reads of a single entry are checked against its key.
+        let reader = *ClientFullId::new_ed25519(&mut rng).public_id().public_key();
+        unwrap!(data.set_user_permissions(
+            User::Key(reader),
+            PermissionSet::new()
+                .allow_for_prefix(Action::Read, b"public/".to_vec())
+                .allow_for_prefix(Action::Read, b"inbox/".to_vec())
+                .deny_for_prefix(Action::Read, b"inbox/sealed/".to_vec()),
+            3,
+            &Limits::default()
+        ));
+        let get_value = |key: &[u8]| Request::GetMDataValue {
+            address,
+            key: key.to_vec(),
+        };
+        unwrap!(data.check_permissions(get_value(b"public/a"), reader));
+        unwrap!(data.check_permissions(get_value(b"inbox/a"), reader));
+        assert_eq!(
+            data.check_permissions(get_value(b"inbox/sealed/a"), reader),
+            Err(Error::AccessDenied)
+        );
+        // Keys out of the reader's scopes fall back to `User::Anyone`.
+        unwrap!(data.check_permissions(get_value(b"other"), reader));
+        unwrap!(data.check_permissions(
+            Request::ListMDataValueHistory {
+                address,
+                key: b"public/a".to_vec(),
+            },
+            reader
+        ));
+        assert_eq!(
+            data.check_permissions(
+                Request::GetMDataValueAtVersion {
+                    address,
+                    key: b"inbox/sealed/a".to_vec(),
+                    version: 0,
+                },
+                reader
+            ),
+            Err(Error::AccessDenied)
+        );
+        // Reads of the whole data are denied by a deny of any scope.
+        assert_eq!(
+            data.check_permissions(Request::GetMData(address), reader),
+            Err(Error::AccessDenied)
+        );
+    }
+
+    #[test]
+    fn effective_permissions() {
+        let mut rng = rand::thread_rng();
+        let owner = *ClientFullId::new_ed25519(&mut rng).public_id().public_key();
+        let app = *ClientFullId::new_ed25519(&mut rng).public_id().public_key();
+        let stranger = *ClientFullId::new_ed25519(&mut rng).public_id().public_key();
+
+        let mut data = SeqMutableData::new(rand::random(), 100, owner.into());
+        let result = data.effective_permissions(stranger);
+        assert!(result.allowed.is_empty());
+        assert_eq!(result.denied.get(&Action::Read), Some(&Denial::NoEntry));
+
+        unwrap!(data.set_user_permissions(
+            User::Anyone,
+            PermissionSet::new()
+                .allow(Action::Read)
+                .deny(Action::Delete),
+            1,
+            &Limits::default()
+        ));
+        unwrap!(data.set_user_permissions(
+            User::Key(app),
+            PermissionSet::new()
+                .allow(Action::Update)
+                .allow_for_prefix(Action::Insert, b"inbox/".to_vec())
+                .deny(Action::ManagePermissions),
+            2,
+            &Limits::default()
+        ));
+
+        let result = data.effective_permissions(owner);
+        assert_eq!(result.allowed.len(), 5);
+
+        let result = data.effective_permissions(app);
+        let allowed: Vec<_> = result.allowed.iter().cloned().collect();
+        assert_eq!(allowed, vec![Action::Read, Action::Update]);
+        let mut prefixes = BTreeSet::new();
+        let _ = prefixes.insert(b"inbox/".to_vec());
+        assert_eq!(
+            result.denied.get(&Action::Insert),
+            Some(&Denial::OnlyForPrefixes(prefixes))
+        );
+        assert_eq!(
+            result.denied.get(&Action::Delete),
+            Some(&Denial::Denied(User::Anyone))
+        );
+        assert_eq!(
+            result.denied.get(&Action::ManagePermissions),
+            Some(&Denial::Denied(User::Key(app)))
+        );
+
+        let result = data.effective_permissions(stranger);
+        assert!(result.allowed.contains(&Action::Read));
+        assert_eq!(
+            result.denied.get(&Action::Update),
+            Some(&Denial::NotAllowed)
+        );
+
+        // The result agrees with the checks made on requests.
+        let address = *data.address();
+        for key in &[owner, app, stranger] {
+            let allowed = data.effective_permissions(*key).allowed;
+            assert_eq!(
+                data.check_permissions(Request::GetMData(address), *key)
+                    .is_ok(),
+                allowed.contains(&Action::Read)
+            );
+            let del_permissions = Request::DelMDataUserPermissions {
+                address,
+                user: User::Anyone,
+                version: 3,
+            };
+            assert_eq!(
+                data.check_permissions(del_permissions, *key).is_ok(),
+                allowed.contains(&Action::ManagePermissions)
+            );
+        }
+    }
 }
@@ -0,0 +1,243 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{UnseqEntryActions, UnseqMutableData};
+use crate::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Lamport timestamp of a change to an entry of an `UnseqReplica`.
+///
+/// Timestamps are ordered by `counter` first, so that a change made after observing another one
+/// always wins over it, and then by `actor` to order concurrent changes.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Timestamp {
+    /// Lamport counter of the change.
+    pub counter: u64,
+    /// Key of the actor which made the change, or `None` for entries taken from a snapshot of the
+    /// network data.
+    pub actor: Option<PublicKey>,
+}
+
+/// Replica of the entries of an `UnseqMutableData` which can be edited while disconnected and
+/// merged with other replicas.
+///
+/// Entries are last-writer-wins registers: every insert, update and delete is stamped with a
+/// `Timestamp`, and merging keeps the latest change of each entry. Deletes are kept as
+/// tombstones so that they win over older updates. `merge` is commutative, associative and
+/// idempotent, so replicas converge regardless of the order in which they are merged.
+///
+/// The merged state is written back to the network with `MutateUnseqMDataEntries`, using the
+/// actions returned by `entry_actions`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct UnseqReplica {
+    /// Latest change of each entry, with `None` for a deleted entry.
+    entries: BTreeMap<Vec<u8>, (Timestamp, Option<Vec<u8>>)>,
+    /// Highest Lamport counter observed by this replica.
+    clock: u64,
+}
+
+impl UnseqReplica {
+    /// Creates an empty replica.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates a replica holding the current entries of `data`.
+    pub fn from_data(data: &UnseqMutableData) -> Self {
+        let timestamp = Timestamp {
+            counter: 0,
+            actor: None,
+        };
+        Self {
+            entries: data
+                .entries()
+                .iter()
+                .map(|(key, value)| (key.clone(), (timestamp, Some(value.clone()))))
+                .collect(),
+            clock: 0,
+        }
+    }
+
+    /// Returns the value of the given key, unless it is absent or deleted.
+    pub fn get(&self, key: &[u8]) -> Option<&Vec<u8>> {
+        self.entries.get(key).and_then(|(_, value)| value.as_ref())
+    }
+
+    /// Returns all entries which aren't deleted.
+    pub fn entries(&self) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        self.entries
+            .iter()
+            .filter_map(|(key, (_, value))| {
+                value.as_ref().map(|value| (key.clone(), value.clone()))
+            })
+            .collect()
+    }
+
+    /// Inserts or updates an entry on behalf of `actor`.
+    pub fn insert(&mut self, actor: PublicKey, key: Vec<u8>, value: Vec<u8>) -> Timestamp {
+        self.write(actor, key, Some(value))
+    }
+
+    /// Deletes an entry on behalf of `actor`.
+    pub fn remove(&mut self, actor: PublicKey, key: Vec<u8>) -> Timestamp {
+        self.write(actor, key, None)
+    }
+
+    /// Merges the changes of `other` into this replica.
+    pub fn merge(&mut self, other: &Self) {
+        for (key, change) in &other.entries {
+            let _ = self
+                .entries
+                .entry(key.clone())
+                .and_modify(|current| {
+                    // Compare the values too, so that concurrent changes with equal timestamps
+                    // (e.g. different snapshots) resolve the same way on every replica.
+                    if *change > *current {
+                        *current = change.clone();
+                    }
+                })
+                .or_insert_with(|| change.clone());
+        }
+        self.clock = self.clock.max(other.clock);
+    }
+
+    /// Computes the entry actions which bring `data` up to date with this replica.
+    ///
+    /// Entries of `data` which this replica has never seen are left untouched.
+    pub fn entry_actions(&self, data: &UnseqMutableData) -> UnseqEntryActions {
+        let mut actions = UnseqEntryActions::new();
+        for (key, (_, value)) in &self.entries {
+            actions = match (data.get(key), value) {
+                (None, Some(value)) => actions.ins(key.clone(), value.clone()),
+                (Some(current), Some(value)) if current != value => {
+                    actions.update(key.clone(), value.clone())
+                }
+                (Some(_), None) => actions.del(key.clone()),
+                _ => actions,
+            };
+        }
+        actions
+    }
+
+    fn write(&mut self, actor: PublicKey, key: Vec<u8>, value: Option<Vec<u8>>) -> Timestamp {
+        self.clock += 1;
+        let timestamp = Timestamp {
+            counter: self.clock,
+            actor: Some(actor),
+        };
+        let _ = self.entries.insert(key, (timestamp, value));
+        timestamp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{utils, ClientFullId};
+    use rand::Rng;
+    use unwrap::unwrap;
+
+    fn random_actor() -> PublicKey {
+        *ClientFullId::new_ed25519(&mut rand::thread_rng())
+            .public_id()
+            .public_key()
+    }
+
+    // Builds a replica by applying random edits of `actor` to a random merge of `sources`.
+    fn random_replica<R: Rng>(
+        rng: &mut R,
+        actor: PublicKey,
+        sources: &[UnseqReplica],
+    ) -> UnseqReplica {
+        let mut replica = UnseqReplica::new();
+        for source in sources {
+            if rng.gen() {
+                replica.merge(source);
+            }
+        }
+        for _ in 0..rng.gen_range(0, 8) {
+            let key = vec![rng.gen_range(0, 4)];
+            if rng.gen_range(0, 4) == 0 {
+                let _ = replica.remove(actor, key);
+            } else {
+                let _ = replica.insert(actor, key, vec![rng.gen_range(0, 4)]);
+            }
+        }
+        replica
+    }
+
+    fn merged(replicas: &[&UnseqReplica]) -> UnseqReplica {
+        let mut result = UnseqReplica::new();
+        for replica in replicas {
+            result.merge(replica);
+        }
+        result
+    }
+
+    #[test]
+    fn merge_properties() {
+        let mut rng = utils::get_rng();
+        let actors = [random_actor(), random_actor(), random_actor()];
+
+        for _ in 0..200 {
+            let mut replicas = Vec::new();
+            for actor in &actors {
+                let replica = random_replica(&mut rng, *actor, &replicas);
+                replicas.push(replica);
+            }
+            let (a, b, c) = (&replicas[0], &replicas[1], &replicas[2]);
+
+            // Commutative
+            assert_eq!(merged(&[a, b]), merged(&[b, a]));
+            // Associative
+            assert_eq!(
+                merged(&[&merged(&[a, b]), c]),
+                merged(&[a, &merged(&[b, c])])
+            );
+            // Idempotent
+            assert_eq!(merged(&[a, a]), merged(&[a]));
+            let mut ab = merged(&[a, b]);
+            ab.merge(b);
+            assert_eq!(ab, merged(&[a, b]));
+        }
+    }
+
+    #[test]
+    fn last_writer_wins() {
+        let (alice, bob) = (random_actor(), random_actor());
+        let mut data = UnseqMutableData::new(rand::random(), 100, alice.into());
+        let actions = UnseqEntryActions::new()
+            .ins(b"a".to_vec(), b"0".to_vec())
+            .ins(b"b".to_vec(), b"0".to_vec());
+        unwrap!(data.mutate_entries(actions, alice));
+
+        let mut replica_a = UnseqReplica::from_data(&data);
+        let mut replica_b = replica_a.clone();
+
+        // Bob deletes an entry which Alice then updates, after seeing Bob's earlier changes.
+        let _ = replica_b.remove(bob, b"a".to_vec());
+        let _ = replica_b.insert(bob, b"c".to_vec(), b"bob".to_vec());
+        replica_a.merge(&replica_b);
+        let _ = replica_a.insert(alice, b"a".to_vec(), b"alice".to_vec());
+        let _ = replica_b.remove(bob, b"b".to_vec());
+
+        replica_a.merge(&replica_b);
+        replica_b.merge(&replica_a);
+        assert_eq!(replica_a, replica_b);
+        assert_eq!(replica_a.get(b"a"), Some(&b"alice".to_vec()));
+        assert_eq!(replica_a.get(b"b"), None);
+
+        // The merged state is written back through the usual entry actions.
+        let actions = replica_a.entry_actions(&data);
+        unwrap!(data.mutate_entries(actions, alice));
+        assert_eq!(*data.entries(), replica_a.entries());
+        assert!(replica_a.entry_actions(&data).is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Error, PublicKey, Result, Signature};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A set of owner keys together with an M-of-N signing policy.
+///
+/// Owner-only operations on data owned by an `OwnerSet` have to be approved by at least
+/// `threshold` of its keys. A single key is an `OwnerSet` with a threshold of 1.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize)]
+pub struct OwnerSet {
+    keys: BTreeSet<PublicKey>,
+    threshold: usize,
+}
+
+impl OwnerSet {
+    /// Creates a new owner set requiring `threshold` of `keys` to approve owner operations.
+    ///
+    /// Returns `Error::InvalidOwners` if `threshold` is zero or larger than the number of keys.
+    pub fn new(keys: BTreeSet<PublicKey>, threshold: usize) -> Result<Self> {
+        let owners = Self { keys, threshold };
+        owners.validate()?;
+        Ok(owners)
+    }
+
+    /// Checks that the threshold is neither zero nor larger than the number of keys.
+    pub fn validate(&self) -> Result<()> {
+        if self.threshold == 0 || self.threshold > self.keys.len() {
+            Err(Error::InvalidOwners)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the owner keys.
+    pub fn keys(&self) -> &BTreeSet<PublicKey> {
+        &self.keys
+    }
+
+    /// Returns the number of owners required to approve an owner operation.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Returns true if `key` is one of the owners.
+    pub fn contains(&self, key: &PublicKey) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Returns true if `signers` contains at least `threshold` distinct owners.
+    pub fn is_satisfied_by<'a, I>(&self, signers: I) -> bool
+    where
+        I: IntoIterator<Item = &'a PublicKey>,
+    {
+        let signers: BTreeSet<_> = signers
+            .into_iter()
+            .filter(|key| self.keys.contains(key))
+            .collect();
+        signers.len() >= self.threshold
+    }
+
+    /// Verifies that `signatures` over `data` satisfy the signing policy.
+    ///
+    /// Every signature has to be made by one of the owners and be valid, and there have to be at
+    /// least `threshold` of them.
+    pub fn verify<T: AsRef<[u8]>>(
+        &self,
+        data: T,
+        signatures: &BTreeMap<PublicKey, Signature>,
+    ) -> Result<()> {
+        for (key, signature) in signatures {
+            if !self.keys.contains(key) {
+                return Err(Error::InvalidOwners);
+            }
+            key.verify(signature, data.as_ref())?;
+        }
+        if self.is_satisfied_by(signatures.keys()) {
+            Ok(())
+        } else {
+            Err(Error::InvalidOwners)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OwnerSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        // Same layout as `OwnerSet`, so the derived encoding is kept.
+        #[derive(Deserialize)]
+        #[serde(rename = "OwnerSet")]
+        struct Unchecked {
+            keys: BTreeSet<PublicKey>,
+            threshold: usize,
+        }
+
+        let Unchecked { keys, threshold } = Deserialize::deserialize(deserializer)?;
+        let count = keys.len();
+        OwnerSet::new(keys, threshold).map_err(|_| {
+            de::Error::custom(format!(
+                "Invalid OwnerSet threshold {} for {} keys",
+                threshold, count
+            ))
+        })
+    }
+}
+
+impl From<PublicKey> for OwnerSet {
+    fn from(key: PublicKey) -> Self {
+        let mut keys = BTreeSet::new();
+        let _ = keys.insert(key);
+        Self { keys, threshold: 1 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClientFullId;
+    use unwrap::unwrap;
+
+    #[test]
+    fn new_owner_set() {
+        let mut rng = rand::thread_rng();
+        let keys: BTreeSet<_> = (0..3)
+            .map(|_| *ClientFullId::new_bls(&mut rng).public_id().public_key())
+            .collect();
+
+        assert_eq!(OwnerSet::new(keys.clone(), 0), Err(Error::InvalidOwners));
+        assert_eq!(OwnerSet::new(keys.clone(), 4), Err(Error::InvalidOwners));
+        assert_eq!(OwnerSet::new(BTreeSet::new(), 1), Err(Error::InvalidOwners));
+        let owners = unwrap!(OwnerSet::new(keys.clone(), 2));
+        assert_eq!(owners.threshold(), 2);
+        assert!(keys.iter().all(|key| owners.contains(key)));
+
+        let key = unwrap!(keys.iter().next().cloned());
+        let single = OwnerSet::from(key);
+        assert!(single.is_satisfied_by(&[key]));
+        assert!(!owners.is_satisfied_by(&[key, key]));
+    }
+
+    #[test]
+    fn deserialise_validates_threshold() {
+        let mut rng = rand::thread_rng();
+        let keys: BTreeSet<_> = (0..2)
+            .map(|_| *ClientFullId::new_bls(&mut rng).public_id().public_key())
+            .collect();
+        let owners = unwrap!(OwnerSet::new(keys.clone(), 2));
+        let serialised = unwrap!(bincode::serialize(&owners));
+        assert_eq!(
+            unwrap!(bincode::deserialize::<OwnerSet>(&serialised)),
+            owners
+        );
+
+        for threshold in &[0, 3] {
+            let serialised = unwrap!(bincode::serialize(&(&keys, *threshold as u64)));
+            assert!(bincode::deserialize::<OwnerSet>(&serialised).is_err());
+        }
+    }
+
+    #[test]
+    fn verify_signatures() {
+        let mut rng = rand::thread_rng();
+        let ids: Vec<_> = (0..3)
+            .map(|_| ClientFullId::new_ed25519(&mut rng))
+            .collect();
+        let keys = ids.iter().map(|id| *id.public_id().public_key()).collect();
+        let owners = unwrap!(OwnerSet::new(keys, 2));
+        let data = b"new owners";
+
+        let sign = |ids: &[ClientFullId]| -> BTreeMap<_, _> {
+            ids.iter()
+                .map(|id| (*id.public_id().public_key(), id.sign(data)))
+                .collect()
+        };
+
+        assert_eq!(
+            owners.verify(data, &sign(&ids[..1])),
+            Err(Error::InvalidOwners)
+        );
+        unwrap!(owners.verify(data, &sign(&ids[..2])));
+        unwrap!(owners.verify(data, &sign(&ids)));
+
+        // Signature by a non-owner.
+        let stranger = ClientFullId::new_ed25519(&mut rng);
+        let mut signatures = sign(&ids[..2]);
+        let _ = signatures.insert(*stranger.public_id().public_key(), stranger.sign(data));
+        assert_eq!(owners.verify(data, &signatures), Err(Error::InvalidOwners));
+
+        // Signature over different data.
+        let mut signatures = sign(&ids[..1]);
+        let _ = signatures.insert(*ids[1].public_id().public_key(), ids[1].sign(b"other"));
+        assert_eq!(
+            owners.verify(data, &signatures),
+            Err(Error::InvalidSignature)
+        );
+    }
+}
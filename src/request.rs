@@ -8,19 +8,51 @@
 // Software.
 
 mod account_data;
+mod signed_request;
 
 pub use self::account_data::{AccountData, MAX_ACCOUNT_DATA_BYTES};
+pub use self::signed_request::SignedRequest;
 use crate::{
     AData, ADataAddress, ADataAppend, ADataIndex, ADataOwner, ADataPubPermissions,
-    ADataUnpubPermissions, ADataUser, AppPermissions, Coins, IDataAddress, IDataKind, MDataAddress,
-    MDataPermissionSet, MDataSeqEntryActions, MDataUnseqEntryActions, PublicKey, SeqMutableData,
-    UnseqMutableData, XorName,
+    ADataUnpubPermissions, ADataUser, AppPermissions, Coins, DeletionProof, IDataAddress,
+    IDataKind, MDataAddress, MDataPageQuery, MDataPermissionSet, MDataSeqEntryActions,
+    MDataUnseqEntryActions, MDataUser, MutableData, OwnerSet, PublicKey, ResponseKind,
+    SeqMutableData, Signature, UnseqMutableData, XorName, COST_OF_PUT,
 };
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
 
 pub type TransactionId = u64; // TODO: Use the trait UUID
 
+/// Whether a request reads or mutates the network state.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum RequestType {
+    /// Reads data without changing it.
+    Read,
+    /// Creates, modifies or deletes data, coins or keys.
+    Mutation,
+}
+
+/// The family of data a request operates on.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DataType {
+    /// Immutable Data.
+    IData,
+    /// Mutable Data.
+    MData,
+    /// Append Only Data.
+    AData,
+    /// Coin balances and transactions.
+    Coins,
+    /// Account packets.
+    Account,
+    /// Authorised app keys stored by the client's Elders.
+    AuthKeys,
+}
+
 /// RPC Request that is sent to vaults
 #[allow(clippy::large_enum_variant, missing_docs)]
 #[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
@@ -30,7 +62,8 @@ pub enum Request {
     //
     PutIData(IDataKind),
     GetIData(IDataAddress),
-    DeleteUnpubIData(IDataAddress),
+    /// Delete unpublished `ImmutableData`, with the approval of its owner.
+    DeleteUnpubIData(DeletionProof<IDataAddress>),
     //
     // ===== Mutable Data =====
     //
@@ -49,19 +82,19 @@ pub enum Request {
     ListMDataValues(MDataAddress),
     SetMDataUserPermissions {
         address: MDataAddress,
-        user: PublicKey,
+        user: MDataUser,
         permissions: MDataPermissionSet,
         version: u64,
     },
     DelMDataUserPermissions {
         address: MDataAddress,
-        user: PublicKey,
+        user: MDataUser,
         version: u64,
     },
     ListMDataPermissions(MDataAddress),
     ListMDataUserPermissions {
         address: MDataAddress,
-        user: PublicKey,
+        user: MDataUser,
     },
     MutateSeqMDataEntries {
         address: MDataAddress,
@@ -86,9 +119,9 @@ pub enum Request {
     },
     /// Delete an unpublished unsequenced `AppendOnlyData`.
     ///
-    /// This operation MUST return an error if applied to published AppendOnlyData. Only the current
-    /// owner(s) can perform this action.
-    DeleteAData(ADataAddress),
+    /// This operation MUST return an error if applied to published AppendOnlyData. The proof has
+    /// to be signed by the current owner(s), but the request can be sent by anyone.
+    DeleteAData(DeletionProof<ADataAddress>),
     /// Get a range of entries from an AppendOnlyData object on the network.
     GetADataRange {
         address: ADataAddress,
@@ -205,6 +238,575 @@ pub enum Request {
         /// Incremented version
         version: u64,
     },
+    //
+    // New variants are only ever appended, so that the serialised index of every existing variant
+    // stays the same.
+    //
+    // ===== Multi-owner =====
+    //
+    /// A request approved by further owners of the data, besides the requester.
+    ///
+    /// Each of `signatures` is made over `request_payload(&request, &message_id)`, the same bytes
+    /// the requester signs. Owner-only operations are allowed if the requester together with the
+    /// co-signers satisfy the signing policy of the owners.
+    CoSigned {
+        request: Box<Request>,
+        signatures: BTreeMap<PublicKey, Signature>,
+    },
+    //
+    // ===== Mutable Data entry history =====
+    //
+    /// Get a previous version of a sequenced entry, if it is retained in the history.
+    GetMDataValueAtVersion {
+        address: MDataAddress,
+        key: Vec<u8>,
+        version: u64,
+    },
+    /// List the retained versions of a sequenced entry, oldest first.
+    ListMDataValueHistory {
+        address: MDataAddress,
+        key: Vec<u8>,
+    },
+    //
+    // ===== Mutable Data pagination =====
+    //
+    /// List a page of entries, in key order.
+    ListMDataEntriesPage {
+        address: MDataAddress,
+        query: MDataPageQuery,
+    },
+    /// List a page of keys, in key order.
+    ListMDataKeysPage {
+        address: MDataAddress,
+        query: MDataPageQuery,
+    },
+    /// List a page of values, in key order.
+    ListMDataValuesPage {
+        address: MDataAddress,
+        query: MDataPageQuery,
+    },
+    //
+    // ===== Append Only Data key index =====
+    //
+    /// Get the value of the latest entry with the given key.
+    GetADataValue {
+        address: ADataAddress,
+        key: Vec<u8>,
+    },
+    //
+    // ===== Append Only Data subscriptions =====
+    //
+    /// Subscribe to entries appended to an AppendOnlyData.
+    ///
+    /// The response carries the entries from `from_index` onwards which are already present. Any
+    /// later entries are pushed to the subscriber as `Message::Notification`s.
+    SubscribeAData {
+        address: ADataAddress,
+        from_index: u64,
+    },
+    /// Cancel a subscription made with `SubscribeAData`.
+    UnsubscribeAData(ADataAddress),
+    //
+    // ===== Mutable Data subscriptions =====
+    //
+    /// Subscribe to changes of a MutableData.
+    ///
+    /// The response carries the current data. Every later change is pushed to the subscriber as
+    /// a `Message::Notification`, in the order the changes are applied.
+    SubscribeMData(MDataAddress),
+    /// Cancel a subscription made with `SubscribeMData`.
+    UnsubscribeMData(MDataAddress),
+    //
+    // ===== Mutable Data ownership transfer =====
+    //
+    /// Transfer the ownership of a MutableData to `new_owner`.
+    ///
+    /// `signatures` are made by the current owners over `mdata_owner_change_payload`, and have to
+    /// satisfy their signing policy, so the request can be relayed by anyone. The new owners can
+    /// prove they accept the transfer by signing the same payload in `acceptance`.
+    ChangeMDataOwner {
+        address: MDataAddress,
+        new_owner: OwnerSet,
+        version: u64,
+        signatures: BTreeMap<PublicKey, Signature>,
+        acceptance: Option<BTreeMap<PublicKey, Signature>>,
+    },
+}
+
+impl Request {
+    /// Returns the request to apply: the co-signed request for `CoSigned`, `self` otherwise.
+    pub fn inner(&self) -> &Request {
+        match *self {
+            Request::CoSigned { ref request, .. } => request,
+            _ => self,
+        }
+    }
+
+    /// Returns the keys of the co-signers of a `CoSigned` request, or none for other requests.
+    pub fn co_signers(&self) -> BTreeSet<PublicKey> {
+        match *self {
+            Request::CoSigned { ref signatures, .. } => signatures.keys().cloned().collect(),
+            _ => BTreeSet::new(),
+        }
+    }
+
+    /// Returns whether this request is a read or a mutation.
+    pub fn request_type(&self) -> RequestType {
+        use Request::*;
+        match *self {
+            GetIData(_)
+            | GetMData(_)
+            | GetMDataValue { .. }
+            | GetMDataValueAtVersion { .. }
+            | ListMDataValueHistory { .. }
+            | GetMDataShell(_)
+            | GetMDataVersion(_)
+            | ListMDataEntries(_)
+            | ListMDataKeys(_)
+            | ListMDataValues(_)
+            | ListMDataEntriesPage { .. }
+            | ListMDataKeysPage { .. }
+            | ListMDataValuesPage { .. }
+            | ListMDataPermissions(_)
+            | ListMDataUserPermissions { .. }
+            | SubscribeMData(_)
+            | UnsubscribeMData(_)
+            | GetAData(_)
+            | GetADataShell { .. }
+            | GetADataRange { .. }
+            | GetADataIndices(_)
+            | GetADataLastEntry(_)
+            | GetADataValue { .. }
+            | GetADataPermissions { .. }
+            | GetPubADataUserPermissions { .. }
+            | GetUnpubADataUserPermissions { .. }
+            | GetADataOwners { .. }
+            | SubscribeAData { .. }
+            | UnsubscribeAData(_)
+            | GetTransaction { .. }
+            | GetBalance
+            | GetAccount(_)
+            | ListAuthKeysAndVersion => RequestType::Read,
+            PutIData(_)
+            | DeleteUnpubIData(_)
+            | PutUnseqMData(_)
+            | PutSeqMData(_)
+            | DeleteMData(_)
+            | SetMDataUserPermissions { .. }
+            | DelMDataUserPermissions { .. }
+            | MutateSeqMDataEntries { .. }
+            | MutateUnseqMDataEntries { .. }
+            | ChangeMDataOwner { .. }
+            | PutAData(_)
+            | DeleteAData(_)
+            | AddPubADataPermissions { .. }
+            | AddUnpubADataPermissions { .. }
+            | SetADataOwner { .. }
+            | AppendSeq { .. }
+            | AppendUnseq(_)
+            | TransferCoins { .. }
+            | CreateCoinBalance { .. }
+            | CreateAccount(_)
+            | CreateAccountFor { .. }
+            | UpdateAccount(_)
+            | InsAuthKey { .. }
+            | DelAuthKey { .. } => RequestType::Mutation,
+            CoSigned { ref request, .. } => request.request_type(),
+        }
+    }
+
+    /// Returns true if this request mutates the network state.
+    pub fn is_mutation(&self) -> bool {
+        self.request_type() == RequestType::Mutation
+    }
+
+    /// Returns the family of data this request operates on.
+    pub fn data_type(&self) -> DataType {
+        use Request::*;
+        match *self {
+            PutIData(_) | GetIData(_) | DeleteUnpubIData(_) => DataType::IData,
+            PutUnseqMData(_)
+            | PutSeqMData(_)
+            | GetMData(_)
+            | GetMDataValue { .. }
+            | GetMDataValueAtVersion { .. }
+            | ListMDataValueHistory { .. }
+            | DeleteMData(_)
+            | GetMDataShell(_)
+            | GetMDataVersion(_)
+            | ListMDataEntries(_)
+            | ListMDataKeys(_)
+            | ListMDataValues(_)
+            | ListMDataEntriesPage { .. }
+            | ListMDataKeysPage { .. }
+            | ListMDataValuesPage { .. }
+            | SetMDataUserPermissions { .. }
+            | DelMDataUserPermissions { .. }
+            | ListMDataPermissions(_)
+            | ListMDataUserPermissions { .. }
+            | MutateSeqMDataEntries { .. }
+            | MutateUnseqMDataEntries { .. }
+            | SubscribeMData(_)
+            | ChangeMDataOwner { .. }
+            | UnsubscribeMData(_) => DataType::MData,
+            PutAData(_)
+            | GetAData(_)
+            | GetADataShell { .. }
+            | DeleteAData(_)
+            | GetADataRange { .. }
+            | GetADataIndices(_)
+            | GetADataLastEntry(_)
+            | GetADataValue { .. }
+            | GetADataPermissions { .. }
+            | GetPubADataUserPermissions { .. }
+            | GetUnpubADataUserPermissions { .. }
+            | GetADataOwners { .. }
+            | AddPubADataPermissions { .. }
+            | AddUnpubADataPermissions { .. }
+            | SetADataOwner { .. }
+            | AppendSeq { .. }
+            | AppendUnseq(_)
+            | SubscribeAData { .. }
+            | UnsubscribeAData(_) => DataType::AData,
+            TransferCoins { .. }
+            | GetTransaction { .. }
+            | GetBalance
+            | CreateCoinBalance { .. } => DataType::Coins,
+            CreateAccount(_) | CreateAccountFor { .. } | UpdateAccount(_) | GetAccount(_) => {
+                DataType::Account
+            }
+            ListAuthKeysAndVersion | InsAuthKey { .. } | DelAuthKey { .. } => DataType::AuthKeys,
+            CoSigned { ref request, .. } => request.data_type(),
+        }
+    }
+
+    /// Returns the name of the data, balance or account this request should be routed to.
+    ///
+    /// Returns `None` for requests which are handled by the requester's own Elders, i.e. those
+    /// concerning the requester's balance and authorised keys.
+    pub fn destination(&self) -> Option<XorName> {
+        use Request::*;
+        match *self {
+            PutIData(ref data) => Some(*data.name()),
+            GetIData(ref address) => Some(*address.name()),
+            DeleteUnpubIData(ref proof) => Some(*proof.address.name()),
+            PutUnseqMData(ref data) => Some(*data.name()),
+            PutSeqMData(ref data) => Some(*data.name()),
+            GetMData(ref address)
+            | GetMDataValue { ref address, .. }
+            | GetMDataValueAtVersion { ref address, .. }
+            | ListMDataValueHistory { ref address, .. }
+            | DeleteMData(ref address)
+            | GetMDataShell(ref address)
+            | GetMDataVersion(ref address)
+            | ListMDataEntries(ref address)
+            | ListMDataKeys(ref address)
+            | ListMDataValues(ref address)
+            | ListMDataEntriesPage { ref address, .. }
+            | ListMDataKeysPage { ref address, .. }
+            | ListMDataValuesPage { ref address, .. }
+            | SetMDataUserPermissions { ref address, .. }
+            | DelMDataUserPermissions { ref address, .. }
+            | ListMDataPermissions(ref address)
+            | ListMDataUserPermissions { ref address, .. }
+            | MutateSeqMDataEntries { ref address, .. }
+            | MutateUnseqMDataEntries { ref address, .. }
+            | SubscribeMData(ref address)
+            | ChangeMDataOwner { ref address, .. }
+            | UnsubscribeMData(ref address) => Some(*address.name()),
+            PutAData(ref data) => Some(*data.name()),
+            DeleteAData(ref proof) => Some(*proof.address.name()),
+            GetAData(ref address)
+            | GetADataShell { ref address, .. }
+            | GetADataRange { ref address, .. }
+            | GetADataIndices(ref address)
+            | GetADataLastEntry(ref address)
+            | GetADataValue { ref address, .. }
+            | GetADataPermissions { ref address, .. }
+            | GetPubADataUserPermissions { ref address, .. }
+            | GetUnpubADataUserPermissions { ref address, .. }
+            | GetADataOwners { ref address, .. }
+            | AddPubADataPermissions { ref address, .. }
+            | AddUnpubADataPermissions { ref address, .. }
+            | SetADataOwner { ref address, .. }
+            | SubscribeAData { ref address, .. }
+            | UnsubscribeAData(ref address) => Some(*address.name()),
+            AppendSeq { ref append, .. } | AppendUnseq(ref append) => Some(*append.address.name()),
+            TransferCoins {
+                ref destination, ..
+            } => Some(*destination),
+            GetTransaction {
+                ref coins_balance_id,
+                ..
+            } => Some(*coins_balance_id),
+            CreateCoinBalance {
+                ref new_balance_owner,
+                ..
+            } => Some(XorName::from(*new_balance_owner)),
+            CreateAccount(ref account) | UpdateAccount(ref account) => Some(*account.destination()),
+            CreateAccountFor {
+                ref new_account, ..
+            } => Some(*new_account.destination()),
+            GetAccount(ref name) => Some(*name),
+            GetBalance | ListAuthKeysAndVersion | InsAuthKey { .. } | DelAuthKey { .. } => None,
+            CoSigned { ref request, .. } => request.destination(),
+        }
+    }
+
+    /// Returns true if this request must be signed by the requester.
+    ///
+    /// Only reads of published data can be sent unsigned.
+    pub fn requires_signature(&self) -> bool {
+        use Request::*;
+        match *self {
+            GetIData(ref address) => !address.published(),
+            GetAData(ref address)
+            | GetADataShell { ref address, .. }
+            | GetADataRange { ref address, .. }
+            | GetADataIndices(ref address)
+            | GetADataLastEntry(ref address)
+            | GetADataValue { ref address, .. }
+            | GetADataPermissions { ref address, .. }
+            | GetPubADataUserPermissions { ref address, .. }
+            | GetADataOwners { ref address, .. } => !address.is_pub(),
+            PutIData(_)
+            | DeleteUnpubIData(_)
+            | PutUnseqMData(_)
+            | PutSeqMData(_)
+            | GetMData(_)
+            | GetMDataValue { .. }
+            | GetMDataValueAtVersion { .. }
+            | ListMDataValueHistory { .. }
+            | DeleteMData(_)
+            | GetMDataShell(_)
+            | GetMDataVersion(_)
+            | ListMDataEntries(_)
+            | ListMDataKeys(_)
+            | ListMDataValues(_)
+            | ListMDataEntriesPage { .. }
+            | ListMDataKeysPage { .. }
+            | ListMDataValuesPage { .. }
+            | SetMDataUserPermissions { .. }
+            | DelMDataUserPermissions { .. }
+            | ListMDataPermissions(_)
+            | ListMDataUserPermissions { .. }
+            | MutateSeqMDataEntries { .. }
+            | MutateUnseqMDataEntries { .. }
+            | ChangeMDataOwner { .. }
+            | SubscribeMData(_)
+            | UnsubscribeMData(_)
+            | PutAData(_)
+            | DeleteAData(_)
+            | GetUnpubADataUserPermissions { .. }
+            | AddPubADataPermissions { .. }
+            | AddUnpubADataPermissions { .. }
+            | SetADataOwner { .. }
+            | AppendSeq { .. }
+            | AppendUnseq(_)
+            | SubscribeAData { .. }
+            | UnsubscribeAData(_)
+            | TransferCoins { .. }
+            | GetTransaction { .. }
+            | GetBalance
+            | CreateCoinBalance { .. }
+            | CreateAccount(_)
+            | CreateAccountFor { .. }
+            | UpdateAccount(_)
+            | GetAccount(_)
+            | ListAuthKeysAndVersion
+            | InsAuthKey { .. }
+            | DelAuthKey { .. }
+            | CoSigned { .. } => true,
+        }
+    }
+
+    /// Returns the amount charged to the requester's balance for this request.
+    ///
+    /// Reads are free. Every mutation which writes IData, MData, AData or account data to the
+    /// network costs `COST_OF_PUT`. Coin operations are free apart from the amount they move,
+    /// and so are changes to the authorised keys, which are stored by the client's own Elders.
+    pub fn cost(&self) -> Coins {
+        use Request::*;
+        match *self {
+            PutIData(_)
+            | DeleteUnpubIData(_)
+            | PutUnseqMData(_)
+            | PutSeqMData(_)
+            | DeleteMData(_)
+            | SetMDataUserPermissions { .. }
+            | DelMDataUserPermissions { .. }
+            | MutateSeqMDataEntries { .. }
+            | MutateUnseqMDataEntries { .. }
+            | ChangeMDataOwner { .. }
+            | PutAData(_)
+            | DeleteAData(_)
+            | AddPubADataPermissions { .. }
+            | AddUnpubADataPermissions { .. }
+            | SetADataOwner { .. }
+            | AppendSeq { .. }
+            | AppendUnseq(_)
+            | CreateAccount(_)
+            | CreateAccountFor { .. }
+            | UpdateAccount(_) => COST_OF_PUT,
+            GetIData(_)
+            | GetMData(_)
+            | GetMDataValue { .. }
+            | GetMDataValueAtVersion { .. }
+            | ListMDataValueHistory { .. }
+            | GetMDataShell(_)
+            | GetMDataVersion(_)
+            | ListMDataEntries(_)
+            | ListMDataKeys(_)
+            | ListMDataValues(_)
+            | ListMDataEntriesPage { .. }
+            | ListMDataKeysPage { .. }
+            | ListMDataValuesPage { .. }
+            | ListMDataPermissions(_)
+            | ListMDataUserPermissions { .. }
+            | SubscribeMData(_)
+            | UnsubscribeMData(_)
+            | GetAData(_)
+            | GetADataShell { .. }
+            | GetADataRange { .. }
+            | GetADataIndices(_)
+            | GetADataLastEntry(_)
+            | GetADataValue { .. }
+            | GetADataPermissions { .. }
+            | GetPubADataUserPermissions { .. }
+            | GetUnpubADataUserPermissions { .. }
+            | GetADataOwners { .. }
+            | SubscribeAData { .. }
+            | UnsubscribeAData(_)
+            | TransferCoins { .. }
+            | GetTransaction { .. }
+            | GetBalance
+            | CreateCoinBalance { .. }
+            | GetAccount(_)
+            | ListAuthKeysAndVersion
+            | InsAuthKey { .. }
+            | DelAuthKey { .. } => Coins::default(),
+            CoSigned { ref request, .. } => request.cost(),
+        }
+    }
+
+    /// Returns the kinds of `Response` which are legal answers to this request.
+    ///
+    /// Where the request address determines the data flavour (e.g. sequenced or unsequenced
+    /// `MutableData`), only the response matching that flavour is expected.
+    pub fn expected_response_kinds(&self) -> &'static [ResponseKind] {
+        use Request::*;
+        match *self {
+            // IData
+            GetIData(_) => &[ResponseKind::GetIData],
+            PutIData(_) | DeleteUnpubIData(_) => &[ResponseKind::Mutation],
+            // MData
+            GetMData(ref address) | SubscribeMData(ref address) => {
+                if address.is_seq() {
+                    &[ResponseKind::GetSeqMData]
+                } else {
+                    &[ResponseKind::GetUnseqMData]
+                }
+            }
+            GetMDataValue { ref address, .. } => {
+                if address.is_seq() {
+                    &[ResponseKind::GetSeqMDataValue]
+                } else {
+                    &[ResponseKind::GetUnseqMDataValue]
+                }
+            }
+            GetMDataValueAtVersion { .. } => &[ResponseKind::GetMDataValueAtVersion],
+            ListMDataValueHistory { .. } => &[ResponseKind::ListMDataValueHistory],
+            GetMDataShell(ref address) => {
+                if address.is_seq() {
+                    &[ResponseKind::GetSeqMDataShell]
+                } else {
+                    &[ResponseKind::GetUnseqMDataShell]
+                }
+            }
+            GetMDataVersion(_) => &[ResponseKind::GetMDataVersion],
+            ListMDataEntries(ref address) => {
+                if address.is_seq() {
+                    &[ResponseKind::ListSeqMDataEntries]
+                } else {
+                    &[ResponseKind::ListUnseqMDataEntries]
+                }
+            }
+            ListMDataKeys(_) => &[ResponseKind::ListMDataKeys],
+            ListMDataValues(ref address) => {
+                if address.is_seq() {
+                    &[ResponseKind::ListSeqMDataValues]
+                } else {
+                    &[ResponseKind::ListUnseqMDataValues]
+                }
+            }
+            ListMDataEntriesPage { ref address, .. } => {
+                if address.is_seq() {
+                    &[ResponseKind::ListSeqMDataEntriesPage]
+                } else {
+                    &[ResponseKind::ListUnseqMDataEntriesPage]
+                }
+            }
+            ListMDataKeysPage { .. } => &[ResponseKind::ListMDataKeysPage],
+            ListMDataValuesPage { ref address, .. } => {
+                if address.is_seq() {
+                    &[ResponseKind::ListSeqMDataValuesPage]
+                } else {
+                    &[ResponseKind::ListUnseqMDataValuesPage]
+                }
+            }
+            ListMDataPermissions(_) => &[ResponseKind::ListMDataPermissions],
+            ListMDataUserPermissions { .. } => &[ResponseKind::ListMDataUserPermissions],
+            PutUnseqMData(_)
+            | PutSeqMData(_)
+            | DeleteMData(_)
+            | SetMDataUserPermissions { .. }
+            | DelMDataUserPermissions { .. }
+            | MutateSeqMDataEntries { .. }
+            | MutateUnseqMDataEntries { .. }
+            | ChangeMDataOwner { .. }
+            | UnsubscribeMData(_) => &[ResponseKind::Mutation],
+            // AData
+            GetAData(_) => &[ResponseKind::GetAData],
+            GetADataShell { .. } => &[ResponseKind::GetADataShell],
+            GetADataRange { .. } => &[ResponseKind::GetADataRange],
+            GetADataIndices(_) => &[ResponseKind::GetADataIndices],
+            GetADataLastEntry(_) => &[ResponseKind::GetADataLastEntry],
+            GetADataValue { .. } => &[ResponseKind::GetADataValue],
+            GetADataPermissions { ref address, .. } => {
+                if address.is_pub() {
+                    &[ResponseKind::GetPubADataPermissionAtIndex]
+                } else {
+                    &[ResponseKind::GetUnpubADataPermissionAtIndex]
+                }
+            }
+            GetPubADataUserPermissions { .. } => &[ResponseKind::GetPubADataUserPermissions],
+            GetUnpubADataUserPermissions { .. } => &[ResponseKind::GetUnpubADataUserPermissions],
+            GetADataOwners { .. } => &[ResponseKind::GetADataOwners],
+            SubscribeAData { .. } => &[ResponseKind::ADataEvent],
+            PutAData(_)
+            | DeleteAData(_)
+            | AddPubADataPermissions { .. }
+            | AddUnpubADataPermissions { .. }
+            | SetADataOwner { .. }
+            | AppendSeq { .. }
+            | AppendUnseq(_)
+            | UnsubscribeAData(_) => &[ResponseKind::Mutation],
+            // Coins
+            GetTransaction { .. } => &[ResponseKind::GetTransaction],
+            GetBalance => &[ResponseKind::GetBalance],
+            TransferCoins { .. } | CreateCoinBalance { .. } => &[ResponseKind::Mutation],
+            // Account
+            GetAccount(_) => &[ResponseKind::GetAccount],
+            CreateAccount(_) | CreateAccountFor { .. } | UpdateAccount(_) => {
+                &[ResponseKind::Mutation]
+            }
+            // Client (Owner) to SrcElders
+            ListAuthKeysAndVersion => &[ResponseKind::ListAuthKeysAndVersion],
+            InsAuthKey { .. } | DelAuthKey { .. } => &[ResponseKind::Mutation],
+            // Multi-owner
+            CoSigned { ref request, .. } => request.expected_response_kinds(),
+        }
+    }
 }
 
 impl fmt::Debug for Request {
@@ -223,18 +825,26 @@ impl fmt::Debug for Request {
                 PutSeqMData(_) => "Request::PutSeqMData",
                 GetMData(_) => "Request::GetMData",
                 GetMDataValue { .. } => "Request::GetMDataValue",
+                GetMDataValueAtVersion { .. } => "Request::GetMDataValueAtVersion",
+                ListMDataValueHistory { .. } => "Request::ListMDataValueHistory",
                 DeleteMData(_) => "Request::DeleteMData",
                 GetMDataShell(_) => "Request::GetMDataShell",
                 GetMDataVersion(_) => "Request::GetMDataVersion",
                 ListMDataEntries(_) => "Request::ListMDataEntries",
                 ListMDataKeys(_) => "Request::ListMDataKeys",
                 ListMDataValues(_) => "Request::ListMDataValues",
+                ListMDataEntriesPage { .. } => "Request::ListMDataEntriesPage",
+                ListMDataKeysPage { .. } => "Request::ListMDataKeysPage",
+                ListMDataValuesPage { .. } => "Request::ListMDataValuesPage",
                 SetMDataUserPermissions { .. } => "Request::SetMDataUserPermissions",
                 DelMDataUserPermissions { .. } => "Request::DelMDataUserPermissions",
                 ListMDataPermissions(_) => "Request::ListMDataPermissions",
                 ListMDataUserPermissions { .. } => "Request::ListMDataUserPermissions",
                 MutateSeqMDataEntries { .. } => "Request::MutateSeqMDataEntries",
                 MutateUnseqMDataEntries { .. } => "Request::MutateUnseqMDataEntries",
+                SubscribeMData(_) => "Request::SubscribeMData",
+                ChangeMDataOwner { .. } => "Request::ChangeMDataOwner",
+                UnsubscribeMData(_) => "Request::UnsubscribeMData",
                 // AData
                 PutAData(_) => "Request::PutAData",
                 GetAData(_) => "Request::GetAData",
@@ -243,6 +853,7 @@ impl fmt::Debug for Request {
                 GetADataRange { .. } => "Request::GetADataRange",
                 GetADataIndices(_) => "Request::GetADataIndices",
                 GetADataLastEntry(_) => "Request::GetADataLastEntry",
+                GetADataValue { .. } => "Request::GetADataValue",
                 GetADataPermissions { .. } => "Request::GetADataPermissions",
                 GetPubADataUserPermissions { .. } => "Request::GetPubADataUserPermissions",
                 GetUnpubADataUserPermissions { .. } => "Request::GetUnpubADataUserPermissions",
@@ -252,6 +863,8 @@ impl fmt::Debug for Request {
                 SetADataOwner { .. } => "Request::SetADataOwner",
                 AppendSeq { .. } => "Request::AppendSeq",
                 AppendUnseq(_) => "Request::AppendUnseq",
+                SubscribeAData { .. } => "Request::SubscribeAData",
+                UnsubscribeAData(_) => "Request::UnsubscribeAData",
                 TransferCoins { .. } => "Request::TransferCoins",
                 GetTransaction { .. } => "Request::GetTransaction",
                 GetBalance => "Request::GetBalance",
@@ -263,7 +876,53 @@ impl fmt::Debug for Request {
                 CreateAccountFor { .. } => "Request::CreateAccountFor",
                 UpdateAccount { .. } => "Request::UpdateAccount",
                 GetAccount(..) => "Request::GetAccount",
+                CoSigned { .. } => "Request::CoSigned",
             }
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImmutableData, MDataAddress};
+    use threshold_crypto::SecretKey;
+
+    #[test]
+    fn classification() {
+        let data = ImmutableData::new(b"value".to_vec());
+        let request = Request::PutIData(data.clone().into());
+        assert_eq!(request.request_type(), RequestType::Mutation);
+        assert_eq!(request.data_type(), DataType::IData);
+        assert_eq!(request.destination(), Some(*data.name()));
+        assert!(request.requires_signature());
+        assert_eq!(request.cost(), COST_OF_PUT);
+
+        let request = Request::GetIData(*data.address());
+        assert_eq!(request.request_type(), RequestType::Read);
+        assert!(!request.is_mutation());
+        assert!(!request.requires_signature());
+        assert_eq!(request.cost(), Coins::default());
+
+        let address = MDataAddress::new_seq(XorName(rand::random()), 100);
+        let request = Request::ListMDataKeys(address);
+        assert_eq!(request.data_type(), DataType::MData);
+        assert_eq!(request.destination(), Some(*address.name()));
+        assert!(request.requires_signature());
+
+        assert_eq!(Request::GetBalance.data_type(), DataType::Coins);
+        assert_eq!(Request::GetBalance.destination(), None);
+    }
+
+    #[test]
+    fn serialised_variant_index() {
+        // Variants of the original wire format keep their index.
+        let serialised = crate::utils::serialise(&Request::ListAuthKeysAndVersion);
+        assert_eq!(serialised[..], 43u32.to_le_bytes()[..]);
+        let serialised = crate::utils::serialise(&Request::DelAuthKey {
+            key: PublicKey::Bls(SecretKey::random().public_key()),
+            version: 0,
+        });
+        assert_eq!(serialised[..4], 45u32.to_le_bytes()[..]);
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{DataType, Request};
+use crate::{
+    utils, AppFullId, ClientFullId, Error, Message, MessageId, NodeFullId, PublicId, Result,
+    Signature,
+};
+use serde::{Deserialize, Serialize};
+
+/// A request signed by its requester, together with the requester's public identity.
+///
+/// The signature covers [`request_payload`](fn.request_payload.html), i.e. the same bytes checked
+/// by [`verify_signature`](fn.verify_signature.html).
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SignedRequest {
+    request: Request,
+    message_id: MessageId,
+    requester: PublicId,
+    signature: Signature,
+}
+
+impl SignedRequest {
+    /// Signs `request` on behalf of a client.
+    pub fn new_client(full_id: &ClientFullId, request: Request, message_id: MessageId) -> Self {
+        let signature = full_id.sign(utils::request_payload(&request, &message_id));
+        Self {
+            request,
+            message_id,
+            requester: PublicId::Client(full_id.public_id().clone()),
+            signature,
+        }
+    }
+
+    /// Signs `request` on behalf of an app.
+    pub fn new_app(full_id: &AppFullId, request: Request, message_id: MessageId) -> Self {
+        let signature = full_id.sign(utils::request_payload(&request, &message_id));
+        Self {
+            request,
+            message_id,
+            requester: PublicId::App(full_id.public_id().clone()),
+            signature,
+        }
+    }
+
+    /// Signs `request` on behalf of a node, using its ed25519 key.
+    pub fn new_node(full_id: &NodeFullId, request: Request, message_id: MessageId) -> Self {
+        let signature = full_id.sign_using_ed25519(utils::request_payload(&request, &message_id));
+        Self {
+            request,
+            message_id,
+            requester: PublicId::Node(full_id.public_id().clone()),
+            signature,
+        }
+    }
+
+    /// Returns the signed request.
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// Returns the message ID the request was signed with.
+    pub fn message_id(&self) -> MessageId {
+        self.message_id
+    }
+
+    /// Returns the public identity of the requester.
+    pub fn requester(&self) -> &PublicId {
+        &self.requester
+    }
+
+    /// Returns the requester's signature.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Verifies the signature against the requester's public key, and checks that the requester
+    /// is allowed to make this kind of request: account and auth key requests can only be made
+    /// by clients.
+    pub fn verify(&self) -> Result<()> {
+        utils::verify_signature(
+            &self.signature,
+            &self.requester.public_key(),
+            &self.request,
+            &self.message_id,
+        )?;
+
+        match (&self.requester, self.request.data_type()) {
+            (PublicId::App(_), DataType::Account)
+            | (PublicId::App(_), DataType::AuthKeys)
+            | (PublicId::Node(_), DataType::Account)
+            | (PublicId::Node(_), DataType::AuthKeys) => Err(Error::AccessDenied),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<SignedRequest> for Message {
+    fn from(signed: SignedRequest) -> Self {
+        Message::Request {
+            request: signed.request,
+            message_id: signed.message_id,
+            signature: Some(signed.signature),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppPermissions, PublicKey};
+    use unwrap::unwrap;
+
+    #[test]
+    fn sign_and_verify() {
+        let mut rng = rand::thread_rng();
+        let client = ClientFullId::new_ed25519(&mut rng);
+        let app = AppFullId::new_bls(&mut rng, client.public_id().clone());
+        let node = NodeFullId::new(&mut rng);
+
+        let request = Request::GetBalance;
+        unwrap!(SignedRequest::new_client(&client, request.clone(), MessageId::new()).verify());
+        unwrap!(SignedRequest::new_app(&app, request.clone(), MessageId::new()).verify());
+        unwrap!(SignedRequest::new_node(&node, request, MessageId::new()).verify());
+
+        // Signed by a different key than the recorded requester.
+        let other = ClientFullId::new_ed25519(&mut rng);
+        let mut signed = SignedRequest::new_client(&client, Request::GetBalance, MessageId::new());
+        signed.requester = PublicId::Client(other.public_id().clone());
+        assert_eq!(signed.verify(), Err(Error::InvalidSignature));
+
+        // Tampered with after signing.
+        let mut signed = SignedRequest::new_client(&client, Request::GetBalance, MessageId::new());
+        signed.message_id = MessageId::new();
+        assert_eq!(signed.verify(), Err(Error::InvalidSignature));
+
+        let message = Message::from(SignedRequest::new_client(
+            &client,
+            Request::GetBalance,
+            MessageId::new(),
+        ));
+        match message {
+            Message::Request {
+                signature: Some(_), ..
+            } => (),
+            _ => panic!("Unexpected message"),
+        }
+    }
+
+    #[test]
+    fn auth_keys_only_by_client() {
+        let mut rng = rand::thread_rng();
+        let client = ClientFullId::new_ed25519(&mut rng);
+        let app = AppFullId::new_bls(&mut rng, client.public_id().clone());
+        let node = NodeFullId::new(&mut rng);
+
+        let request = Request::InsAuthKey {
+            key: *app.public_id().public_key(),
+            version: 1,
+            permissions: AppPermissions::default(),
+        };
+        unwrap!(SignedRequest::new_client(&client, request.clone(), MessageId::new()).verify());
+        assert_eq!(
+            SignedRequest::new_app(&app, request.clone(), MessageId::new()).verify(),
+            Err(Error::AccessDenied)
+        );
+        assert_eq!(
+            SignedRequest::new_node(&node, request, MessageId::new()).verify(),
+            Err(Error::AccessDenied)
+        );
+
+        let request = Request::DelAuthKey {
+            key: PublicKey::from(*node.public_id().ed25519_public_key()),
+            version: 2,
+        };
+        assert_eq!(
+            SignedRequest::new_app(&app, request, MessageId::new()).verify(),
+            Err(Error::AccessDenied)
+        );
+    }
+}
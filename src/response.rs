@@ -8,9 +8,10 @@
 // Software.
 
 use crate::{
-    AData, ADataIndices, ADataOwner, ADataPubPermissionSet, ADataPubPermissions,
-    ADataUnpubPermissionSet, ADataUnpubPermissions, AppPermissions, Coins, Entries, IDataKind,
-    MDataPermissionSet, MDataValue, PublicKey, Result, SeqMutableData, Signature, UnseqMutableData,
+    AData, ADataEvent, ADataIndices, ADataOwner, ADataPubPermissionSet, ADataPubPermissions,
+    ADataUnpubPermissionSet, ADataUnpubPermissions, AppPermissions, Coins, Entries, Error,
+    IDataKind, MDataEvent, MDataPage, MDataPermissionSet, MDataUser, MDataValue, PublicKey,
+    Request, Result, SeqMutableData, Signature, UnseqMutableData,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
@@ -50,7 +51,7 @@ pub enum Response {
     ListSeqMDataValues(Result<Vec<MDataValue>>),
     ListUnseqMDataValues(Result<Vec<Vec<u8>>>),
     ListMDataUserPermissions(Result<MDataPermissionSet>),
-    ListMDataPermissions(Result<BTreeMap<PublicKey, MDataPermissionSet>>),
+    ListMDataPermissions(Result<BTreeMap<MDataUser, MDataPermissionSet>>),
     GetSeqMDataValue(Result<MDataValue>),
     GetUnseqMDataValue(Result<Vec<u8>>),
     //
@@ -84,6 +85,186 @@ pub enum Response {
     //
     /// Returns a success or failure status for a mutation operation.
     Mutation(Result<()>),
+    //
+    // New variants are only ever appended, so that the serialised index of every existing variant
+    // stays the same.
+    //
+    // ===== Mutable Data entry history =====
+    //
+    GetMDataValueAtVersion(Result<MDataValue>),
+    ListMDataValueHistory(Result<Vec<MDataValue>>),
+    //
+    // ===== Mutable Data pagination =====
+    //
+    ListUnseqMDataEntriesPage(Result<MDataPage<BTreeMap<Vec<u8>, Vec<u8>>>>),
+    ListSeqMDataEntriesPage(Result<MDataPage<BTreeMap<Vec<u8>, MDataValue>>>),
+    ListMDataKeysPage(Result<MDataPage<BTreeSet<Vec<u8>>>>),
+    ListSeqMDataValuesPage(Result<MDataPage<Vec<MDataValue>>>),
+    ListUnseqMDataValuesPage(Result<MDataPage<Vec<Vec<u8>>>>),
+    //
+    // ===== Append Only Data key index =====
+    //
+    GetADataValue(Result<Vec<u8>>),
+    //
+    // ===== Append Only Data subscriptions =====
+    //
+    /// Returns the entries already present when subscribing to an AppendOnlyData.
+    ADataEvent(Result<ADataEvent>),
+}
+
+/// The variant of a `Response`, without its payload.
+#[allow(missing_docs)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum ResponseKind {
+    GetIData,
+    GetUnseqMData,
+    GetSeqMData,
+    GetSeqMDataShell,
+    GetUnseqMDataShell,
+    GetMDataVersion,
+    ListUnseqMDataEntries,
+    ListSeqMDataEntries,
+    ListMDataKeys,
+    ListSeqMDataValues,
+    ListUnseqMDataValues,
+    ListUnseqMDataEntriesPage,
+    ListSeqMDataEntriesPage,
+    ListMDataKeysPage,
+    ListSeqMDataValuesPage,
+    ListUnseqMDataValuesPage,
+    ListMDataUserPermissions,
+    ListMDataPermissions,
+    GetSeqMDataValue,
+    GetUnseqMDataValue,
+    GetMDataValueAtVersion,
+    ListMDataValueHistory,
+    GetAData,
+    GetADataShell,
+    GetADataOwners,
+    GetADataRange,
+    GetADataIndices,
+    GetADataLastEntry,
+    GetADataValue,
+    GetUnpubADataPermissionAtIndex,
+    GetPubADataPermissionAtIndex,
+    GetPubADataUserPermissions,
+    GetUnpubADataUserPermissions,
+    ADataEvent,
+    GetTransaction,
+    GetBalance,
+    ListAuthKeysAndVersion,
+    GetAccount,
+    Mutation,
+}
+
+impl Response {
+    /// Returns the variant of this response.
+    pub fn kind(&self) -> ResponseKind {
+        use Response::*;
+        match *self {
+            GetIData(..) => ResponseKind::GetIData,
+            GetUnseqMData(..) => ResponseKind::GetUnseqMData,
+            GetSeqMData(..) => ResponseKind::GetSeqMData,
+            GetSeqMDataShell(..) => ResponseKind::GetSeqMDataShell,
+            GetUnseqMDataShell(..) => ResponseKind::GetUnseqMDataShell,
+            GetMDataVersion(..) => ResponseKind::GetMDataVersion,
+            ListUnseqMDataEntries(..) => ResponseKind::ListUnseqMDataEntries,
+            ListSeqMDataEntries(..) => ResponseKind::ListSeqMDataEntries,
+            ListMDataKeys(..) => ResponseKind::ListMDataKeys,
+            ListSeqMDataValues(..) => ResponseKind::ListSeqMDataValues,
+            ListUnseqMDataValues(..) => ResponseKind::ListUnseqMDataValues,
+            ListUnseqMDataEntriesPage(..) => ResponseKind::ListUnseqMDataEntriesPage,
+            ListSeqMDataEntriesPage(..) => ResponseKind::ListSeqMDataEntriesPage,
+            ListMDataKeysPage(..) => ResponseKind::ListMDataKeysPage,
+            ListSeqMDataValuesPage(..) => ResponseKind::ListSeqMDataValuesPage,
+            ListUnseqMDataValuesPage(..) => ResponseKind::ListUnseqMDataValuesPage,
+            ListMDataUserPermissions(..) => ResponseKind::ListMDataUserPermissions,
+            ListMDataPermissions(..) => ResponseKind::ListMDataPermissions,
+            GetSeqMDataValue(..) => ResponseKind::GetSeqMDataValue,
+            GetUnseqMDataValue(..) => ResponseKind::GetUnseqMDataValue,
+            GetMDataValueAtVersion(..) => ResponseKind::GetMDataValueAtVersion,
+            ListMDataValueHistory(..) => ResponseKind::ListMDataValueHistory,
+            GetAData(..) => ResponseKind::GetAData,
+            GetADataShell(..) => ResponseKind::GetADataShell,
+            GetADataOwners(..) => ResponseKind::GetADataOwners,
+            GetADataRange(..) => ResponseKind::GetADataRange,
+            GetADataIndices(..) => ResponseKind::GetADataIndices,
+            GetADataLastEntry(..) => ResponseKind::GetADataLastEntry,
+            GetADataValue(..) => ResponseKind::GetADataValue,
+            GetUnpubADataPermissionAtIndex(..) => ResponseKind::GetUnpubADataPermissionAtIndex,
+            GetPubADataPermissionAtIndex(..) => ResponseKind::GetPubADataPermissionAtIndex,
+            GetPubADataUserPermissions(..) => ResponseKind::GetPubADataUserPermissions,
+            GetUnpubADataUserPermissions(..) => ResponseKind::GetUnpubADataUserPermissions,
+            ADataEvent(..) => ResponseKind::ADataEvent,
+            GetTransaction(..) => ResponseKind::GetTransaction,
+            GetBalance(..) => ResponseKind::GetBalance,
+            ListAuthKeysAndVersion(..) => ResponseKind::ListAuthKeysAndVersion,
+            GetAccount(..) => ResponseKind::GetAccount,
+            Mutation(..) => ResponseKind::Mutation,
+        }
+    }
+
+    /// Returns true if this response is a legal answer to `request`.
+    ///
+    /// Clients should reject responses failing this check with `Error::UnexpectedDataReturned`.
+    pub fn is_valid_for(&self, request: &Request) -> bool {
+        request.expected_response_kinds().contains(&self.kind())
+    }
+
+    /// Returns the error carried by this response, or `None` if it carries a successful result.
+    pub fn into_error(self) -> Option<Error> {
+        use Response::*;
+        match self {
+            GetIData(result) => result.err(),
+            GetUnseqMData(result) => result.err(),
+            GetSeqMData(result) => result.err(),
+            GetSeqMDataShell(result) => result.err(),
+            GetUnseqMDataShell(result) => result.err(),
+            GetMDataVersion(result) => result.err(),
+            ListUnseqMDataEntries(result) => result.err(),
+            ListSeqMDataEntries(result) => result.err(),
+            ListMDataKeys(result) => result.err(),
+            ListSeqMDataValues(result) => result.err(),
+            ListUnseqMDataValues(result) => result.err(),
+            ListUnseqMDataEntriesPage(result) => result.err(),
+            ListSeqMDataEntriesPage(result) => result.err(),
+            ListMDataKeysPage(result) => result.err(),
+            ListSeqMDataValuesPage(result) => result.err(),
+            ListUnseqMDataValuesPage(result) => result.err(),
+            ListMDataUserPermissions(result) => result.err(),
+            ListMDataPermissions(result) => result.err(),
+            GetSeqMDataValue(result) => result.err(),
+            GetUnseqMDataValue(result) => result.err(),
+            GetMDataValueAtVersion(result) => result.err(),
+            ListMDataValueHistory(result) => result.err(),
+            GetAData(result) => result.err(),
+            GetADataShell(result) => result.err(),
+            GetADataOwners(result) => result.err(),
+            GetADataRange(result) => result.err(),
+            GetADataIndices(result) => result.err(),
+            GetADataLastEntry(result) => result.err(),
+            GetADataValue(result) => result.err(),
+            GetUnpubADataPermissionAtIndex(result) => result.err(),
+            GetPubADataPermissionAtIndex(result) => result.err(),
+            GetPubADataUserPermissions(result) => result.err(),
+            GetUnpubADataUserPermissions(result) => result.err(),
+            ADataEvent(result) => result.err(),
+            GetTransaction(result) => result.err(),
+            GetBalance(result) => result.err(),
+            ListAuthKeysAndVersion(result) => result.err(),
+            GetAccount(result) => result.err(),
+            Mutation(result) => result.err(),
+        }
+    }
+}
+
+/// Update pushed by vaults to a subscriber, outside of any request/response exchange.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum Notification {
+    /// New entries were appended to an AppendOnlyData.
+    ADataEvent(ADataEvent),
+    /// A MutableData was changed.
+    MDataEvent(MDataEvent),
 }
 
 use std::fmt;
@@ -108,10 +289,17 @@ impl fmt::Debug for Response {
                 ListMDataKeys(..) => "Response::ListMDataKeys",
                 ListSeqMDataValues(..) => "Response::ListSeqMDataValues",
                 ListUnseqMDataValues(..) => "Response::ListUnseqMDataValues",
+                ListUnseqMDataEntriesPage(..) => "Response::ListUnseqMDataEntriesPage",
+                ListSeqMDataEntriesPage(..) => "Response::ListSeqMDataEntriesPage",
+                ListMDataKeysPage(..) => "Response::ListMDataKeysPage",
+                ListSeqMDataValuesPage(..) => "Response::ListSeqMDataValuesPage",
+                ListUnseqMDataValuesPage(..) => "Response::ListUnseqMDataValuesPage",
                 ListMDataPermissions(..) => "Response::ListMDataPermissions",
                 ListMDataUserPermissions(..) => "Response::ListMDataUserPermissions",
                 GetSeqMDataValue(..) => "Response::GetSeqMDataValue",
                 GetUnseqMDataValue(..) => "Response::GetUnseqMDataValue",
+                GetMDataValueAtVersion(..) => "Response::GetMDataValueAtVersion",
+                ListMDataValueHistory(..) => "Response::ListMDataValueHistory",
                 GetTransaction(..) => "Response::GetTransaction",
                 GetBalance(..) => "Response::GetBalance",
                 ListAuthKeysAndVersion(..) => "Response::ListAuthKeysAndVersion",
@@ -119,15 +307,55 @@ impl fmt::Debug for Response {
                 GetADataRange(..) => "Response::GetADataRange",
                 GetADataIndices(..) => "Response::GetADataIndices",
                 GetADataLastEntry(..) => "Response::GetADataLastEntry",
+                GetADataValue(..) => "Response::GetADataValue",
                 GetUnpubADataPermissionAtIndex(..) => "Response::GetADataPermissionAtIndex",
                 GetPubADataPermissionAtIndex(..) => "Response::GetADataPermissionAtIndex",
                 GetPubADataUserPermissions(..) => "Response::GetPubADataUserPermissions",
                 GetUnpubADataUserPermissions(..) => "Response::GetUnpubADataUserPermissions",
                 GetADataShell(..) => "Response::GetADataShell",
                 GetADataOwners(..) => "Response::GetADataOwners",
+                ADataEvent(..) => "Response::ADataEvent",
                 GetAccount(..) => "Response::GetAccount",
                 Mutation(..) => "Response::Mutation",
             }
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MDataAddress, XorName};
+
+    #[test]
+    fn serialised_variant_index() {
+        // The last variant of the original wire format keeps its index.
+        let serialised = crate::utils::serialise(&Response::Mutation(Ok(())));
+        assert_eq!(serialised[..4], 29u32.to_le_bytes()[..]);
+    }
+
+    #[test]
+    fn validate_response_kind() {
+        let seq_address = MDataAddress::new_seq(XorName(rand::random()), 100);
+        let unseq_address = MDataAddress::new_unseq(XorName(rand::random()), 100);
+
+        let response = Response::GetSeqMData(Err(Error::NoSuchData));
+        assert!(response.is_valid_for(&Request::GetMData(seq_address)));
+        assert!(!response.is_valid_for(&Request::GetMData(unseq_address)));
+        assert!(!response.is_valid_for(&Request::DeleteMData(seq_address)));
+
+        let response = Response::Mutation(Ok(()));
+        assert!(response.is_valid_for(&Request::DeleteMData(seq_address)));
+        assert!(!response.is_valid_for(&Request::GetMData(seq_address)));
+    }
+
+    #[test]
+    fn into_error() {
+        assert_eq!(
+            Response::GetMDataVersion(Err(Error::AccessDenied)).into_error(),
+            Some(Error::AccessDenied)
+        );
+        assert_eq!(Response::GetMDataVersion(Ok(1)).into_error(), None);
+        assert_eq!(Response::Mutation(Ok(())).into_error(), None);
+    }
+}
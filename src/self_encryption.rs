@@ -0,0 +1,280 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+mod stream;
+
+pub use self::stream::{ChunkReader, ChunkStore, ChunkWriter};
+use crate::{Error, IDataAddress, ImmutableData, Result, XorName};
+use aes_ctr::{
+    stream_cipher::{generic_array::GenericArray, NewStreamCipher, SyncStreamCipher},
+    Aes256Ctr,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io::Write};
+use unwrap::unwrap;
+
+/// Maximum size of the content of a single chunk.
+pub const MAX_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Minimum size of the content of a single chunk, which guarantees that data maps shrink when
+/// they are stored as chunks themselves.
+pub const MIN_CHUNK_SIZE: usize = 1024;
+
+/// Description of a chunk of content stored as `ImmutableData`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct ChunkInfo {
+    /// Name of the `ImmutableData` holding the chunk.
+    pub name: XorName,
+    /// Hash of the chunk content, before encryption.
+    pub source_hash: [u8; 32],
+    /// Size of the chunk content.
+    pub size: u64,
+}
+
+/// Describes how to reassemble content from its chunks.
+///
+/// The data map of encrypted content holds the keys to its chunks, so it has to be kept as secret
+/// as the content itself.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum DataMap {
+    /// The chunks of the content, in order.
+    Chunks {
+        chunks: Vec<ChunkInfo>,
+        encrypted: bool,
+    },
+    /// A data map which was too large to be used directly, stored as chunks described by the
+    /// inner data map.
+    Child(Box<DataMap>),
+}
+
+impl DataMap {
+    /// Reassembles the content described by this data map, fetching its chunks with `fetch`.
+    ///
+    /// Every chunk is verified against its name and source hash. Returns `Error::InvalidChunk` if
+    /// a fetched chunk doesn't match, and any error returned by `fetch`.
+    pub fn join<F>(&self, mut fetch: F) -> Result<Vec<u8>>
+    where
+        F: FnMut(&XorName) -> Result<ImmutableData>,
+    {
+        self.join_with(&mut fetch)
+    }
+
+    fn join_with(
+        &self,
+        fetch: &mut dyn FnMut(&XorName) -> Result<ImmutableData>,
+    ) -> Result<Vec<u8>> {
+        match self {
+            DataMap::Chunks { chunks, encrypted } => {
+                let mut content = Vec::new();
+                for index in 0..chunks.len() {
+                    let data = fetch(&chunks[index].name)?;
+                    content.extend(decrypt_chunk(chunks, *encrypted, index, &data)?);
+                }
+                Ok(content)
+            }
+            DataMap::Child(data_map) => {
+                let serialised = data_map.join_with(fetch)?;
+                let data_map: DataMap = bincode::deserialize(&serialised)
+                    .map_err(|error| Error::FailedToParse(error.to_string()))?;
+                data_map.join_with(fetch)
+            }
+        }
+    }
+
+    /// Returns the size of the content described by this data map, or `None` if it is only known
+    /// after fetching the child data map.
+    pub fn content_size(&self) -> Option<u64> {
+        match self {
+            DataMap::Chunks { chunks, .. } => Some(chunks.iter().map(|chunk| chunk.size).sum()),
+            DataMap::Child(_) => None,
+        }
+    }
+}
+
+/// Splits content into `ImmutableData` chunks, optionally encrypting them.
+///
+/// Chunks are encrypted with keys derived from the hashes of their own content and of the content
+/// of the two preceding chunks, so identical content always produces identical chunks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SelfEncryptor {
+    chunk_size: usize,
+    encrypt: bool,
+}
+
+impl Default for SelfEncryptor {
+    fn default() -> Self {
+        SelfEncryptor {
+            chunk_size: MAX_CHUNK_SIZE,
+            encrypt: false,
+        }
+    }
+}
+
+impl SelfEncryptor {
+    /// Creates a self encryptor which produces unencrypted chunks of `MAX_CHUNK_SIZE` bytes.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Produces chunks of `chunk_size` bytes, except for the last one.
+    ///
+    /// Returns `Error::ExceededSize` if `chunk_size` is outside `MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE`.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Result<Self> {
+        if !(MIN_CHUNK_SIZE..=MAX_CHUNK_SIZE).contains(&chunk_size) {
+            return Err(Error::ExceededSize);
+        }
+        self.chunk_size = chunk_size;
+        Ok(self)
+    }
+
+    /// Encrypts the chunks.
+    pub fn encrypted(mut self) -> Self {
+        self.encrypt = true;
+        self
+    }
+
+    /// Splits `content` into chunks, and returns them with the data map needed to reassemble it.
+    ///
+    /// If the data map would be larger than a chunk, it is split too and the returned data map
+    /// describes its chunks instead. Use a `ChunkWriter` to split content without holding all of
+    /// it in memory.
+    pub fn split(&self, content: &[u8]) -> (DataMap, Vec<ImmutableData>) {
+        let mut chunks = BTreeMap::new();
+        let mut writer = ChunkWriter::new(&mut chunks, *self);
+        // Writing to a map can't fail.
+        unwrap!(writer.write_all(content));
+        let data_map = unwrap!(writer.finish());
+        (data_map, chunks.values().cloned().collect())
+    }
+}
+
+// Verifies the chunk at `index` in `chunks`, fetched as `data`, and returns its content.
+pub(crate) fn decrypt_chunk(
+    chunks: &[ChunkInfo],
+    encrypted: bool,
+    index: usize,
+    data: &ImmutableData,
+) -> Result<Vec<u8>> {
+    let chunk = &chunks[index];
+    if *data.address() != IDataAddress::Pub(chunk.name) {
+        return Err(Error::InvalidChunk(chunk.name));
+    }
+    let mut content = data.value().clone();
+    if encrypted {
+        apply_keystream(&mut content, &chunk_key(chunks, index));
+    }
+    if content.len() as u64 != chunk.size || tiny_keccak::sha3_256(&content) != chunk.source_hash {
+        return Err(Error::InvalidChunk(chunk.name));
+    }
+    Ok(content)
+}
+
+// Derives the key of the chunk at `index` from its own source hash and those of the two preceding
+// chunks, wrapping around to the last chunks for the first ones.
+fn chunk_key(chunks: &[ChunkInfo], index: usize) -> [u8; 32] {
+    let len = chunks.len();
+    let mut input = Vec::with_capacity(3 * 32);
+    input.extend_from_slice(&chunks[index].source_hash);
+    input.extend_from_slice(&chunks[(index + len - 1) % len].source_hash);
+    input.extend_from_slice(&chunks[(index + 2 * len - 2) % len].source_hash);
+    tiny_keccak::sha3_256(&input)
+}
+
+// Encrypts or decrypts `content` with AES-256 in counter mode. Every chunk has a key of its own,
+// so the nonce is fixed.
+fn apply_keystream(content: &mut [u8], key: &[u8; 32]) {
+    let nonce = [0; 16];
+    let mut cipher = Aes256Ctr::new(
+        GenericArray::from_slice(key),
+        GenericArray::from_slice(&nonce),
+    );
+    cipher.apply_keystream(content);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use unwrap::unwrap;
+
+    fn store(chunks: Vec<ImmutableData>) -> BTreeMap<XorName, ImmutableData> {
+        chunks
+            .into_iter()
+            .map(|data| (*data.name(), data))
+            .collect()
+    }
+
+    #[test]
+    fn split_and_join() {
+        let content: Vec<u8> = (0..20_000).map(|_| rand::random()).collect();
+
+        for encryptor in &[
+            unwrap!(SelfEncryptor::new().with_chunk_size(MIN_CHUNK_SIZE)),
+            unwrap!(SelfEncryptor::new().with_chunk_size(MIN_CHUNK_SIZE)).encrypted(),
+        ] {
+            let (data_map, chunks) = encryptor.split(&content);
+            // 20 chunks don't fit in one, so the data map is stored as chunks too.
+            assert!(chunks.len() > 20);
+            match data_map {
+                DataMap::Child(_) => (),
+                DataMap::Chunks { .. } => panic!("Expected a child data map"),
+            }
+            assert!(chunks
+                .iter()
+                .all(|chunk| chunk.payload_size() <= MIN_CHUNK_SIZE));
+
+            let store = store(chunks);
+            let joined =
+                unwrap!(data_map.join(|name| store.get(name).cloned().ok_or(Error::NoSuchData)));
+            assert_eq!(joined, content);
+        }
+
+        // Encrypted chunks don't expose the content.
+        let encryptor = SelfEncryptor::new().encrypted();
+        let (_, chunks) = encryptor.split(b"plain text");
+        assert_ne!(chunks[0].value(), b"plain text");
+        // Splitting is deterministic.
+        assert_eq!(encryptor.split(b"plain text").1, chunks);
+
+        assert_eq!(
+            SelfEncryptor::new().with_chunk_size(MAX_CHUNK_SIZE + 1),
+            Err(Error::ExceededSize)
+        );
+    }
+
+    #[test]
+    fn invalid_chunks() {
+        let content: Vec<u8> = (0..3000).map(|_| rand::random()).collect();
+        let encryptor = unwrap!(SelfEncryptor::new().with_chunk_size(MIN_CHUNK_SIZE)).encrypted();
+        let (data_map, chunks) = encryptor.split(&content);
+        assert_eq!(data_map.content_size(), Some(3000));
+        let names: Vec<_> = match data_map {
+            DataMap::Chunks { ref chunks, .. } => chunks.iter().map(|chunk| chunk.name).collect(),
+            DataMap::Child(_) => panic!("Unexpected child data map"),
+        };
+        let store = store(chunks);
+
+        // The first chunk returned for every name.
+        let first = unwrap!(store.get(&names[0]).cloned());
+        let result = data_map.join(|_| Ok(first.clone()));
+        assert_eq!(result, Err(Error::InvalidChunk(names[1])));
+
+        // A chunk whose size doesn't match the content.
+        let mut tampered = data_map.clone();
+        if let DataMap::Chunks { ref mut chunks, .. } = tampered {
+            chunks[1].size -= 1;
+        }
+        let result = tampered.join(|name| store.get(name).cloned().ok_or(Error::NoSuchData));
+        assert_eq!(result, Err(Error::InvalidChunk(names[1])));
+
+        // Missing chunks.
+        let result = data_map.join(|_| Err(Error::NoSuchData));
+        assert_eq!(result, Err(Error::NoSuchData));
+    }
+}
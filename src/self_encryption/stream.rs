@@ -0,0 +1,460 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{apply_keystream, chunk_key, decrypt_chunk, ChunkInfo, DataMap, SelfEncryptor};
+use crate::{utils, Error, ImmutableData, Result, XorName};
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Seek, SeekFrom, Write},
+    mem,
+};
+
+/// Storage of the chunks read by `ChunkReader` and written by `ChunkWriter`.
+pub trait ChunkStore {
+    /// Fetches the chunk with the given name.
+    fn get(&mut self, name: &XorName) -> Result<ImmutableData>;
+
+    /// Stores a chunk. Storing the same chunk again has no effect.
+    fn put(&mut self, data: ImmutableData) -> Result<()>;
+}
+
+impl ChunkStore for BTreeMap<XorName, ImmutableData> {
+    fn get(&mut self, name: &XorName) -> Result<ImmutableData> {
+        BTreeMap::get(self, name).cloned().ok_or(Error::NoSuchData)
+    }
+
+    fn put(&mut self, data: ImmutableData) -> Result<()> {
+        let _ = self.insert(*data.name(), data);
+        Ok(())
+    }
+}
+
+impl<S: ChunkStore + ?Sized> ChunkStore for &mut S {
+    fn get(&mut self, name: &XorName) -> Result<ImmutableData> {
+        (**self).get(name)
+    }
+
+    fn put(&mut self, data: ImmutableData) -> Result<()> {
+        (**self).put(data)
+    }
+}
+
+fn io_error(error: Error) -> io::Error {
+    let kind = match error {
+        Error::InvalidChunk(_) => io::ErrorKind::InvalidData,
+        Error::NoSuchData => io::ErrorKind::NotFound,
+        _ => io::ErrorKind::Other,
+    };
+    io::Error::new(kind, error)
+}
+
+/// Reads content described by a `DataMap`, fetching its chunks from a `ChunkStore` as they are
+/// needed.
+///
+/// Only the chunk at the current position is kept in memory. Every chunk is verified against its
+/// name and source hash when it is fetched, and a mismatch is reported as an `io::Error` of kind
+/// `InvalidData`.
+pub struct ChunkReader<S: ChunkStore> {
+    store: S,
+    chunks: Vec<ChunkInfo>,
+    encrypted: bool,
+    // Offset of the first byte of each chunk in the content.
+    offsets: Vec<u64>,
+    len: u64,
+    position: u64,
+    // The most recently fetched chunk and its index.
+    current: Option<(usize, Vec<u8>)>,
+}
+
+impl<S: ChunkStore> ChunkReader<S> {
+    /// Creates a reader of the content described by `data_map`.
+    ///
+    /// A child data map is fetched and resolved straight away.
+    pub fn new(mut store: S, data_map: &DataMap) -> Result<Self> {
+        let (chunks, encrypted) = match data_map {
+            DataMap::Chunks { chunks, encrypted } => (chunks.clone(), *encrypted),
+            DataMap::Child(child) => {
+                let serialised = child.join(|name| store.get(name))?;
+                let data_map: DataMap = bincode::deserialize(&serialised)
+                    .map_err(|error| Error::FailedToParse(error.to_string()))?;
+                return ChunkReader::new(store, &data_map);
+            }
+        };
+        let mut offsets = Vec::with_capacity(chunks.len());
+        let mut len = 0u64;
+        for chunk in &chunks {
+            offsets.push(len);
+            len = len.checked_add(chunk.size).ok_or(Error::ExceededSize)?;
+        }
+        Ok(Self {
+            store,
+            chunks,
+            encrypted,
+            offsets,
+            len,
+            position: 0,
+            current: None,
+        })
+    }
+
+    /// Returns the size of the content.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the content is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the underlying store.
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    // Returns the chunk at `index`, fetching it unless it is the current one.
+    fn chunk(&mut self, index: usize) -> Result<&[u8]> {
+        let fetched = match self.current {
+            Some((current, _)) => current == index,
+            None => false,
+        };
+        if !fetched {
+            let data = self.store.get(&self.chunks[index].name)?;
+            let content = decrypt_chunk(&self.chunks, self.encrypted, index, &data)?;
+            self.current = Some((index, content));
+        }
+        match self.current {
+            Some((_, ref content)) => Ok(content),
+            None => Err(Error::NoSuchData),
+        }
+    }
+}
+
+impl<S: ChunkStore> Read for ChunkReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        // The last chunk starting at or before the position.
+        let index = match self.offsets.binary_search(&self.position) {
+            Ok(index) => index,
+            Err(index) => index - 1,
+        };
+        let start = (self.position - self.offsets[index]) as usize;
+        let chunk = self.chunk(index).map_err(io_error)?;
+        let count = buf.len().min(chunk.len() - start);
+        buf[..count].copy_from_slice(&chunk[start..start + count]);
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl<S: ChunkStore> Seek for ChunkReader<S> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => offset_by(self.len, offset),
+            SeekFrom::Current(offset) => offset_by(self.position, offset),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+fn offset_by(base: u64, offset: i64) -> Option<u64> {
+    if offset < 0 {
+        base.checked_sub(offset.wrapping_neg() as u64)
+    } else {
+        base.checked_add(offset as u64)
+    }
+}
+
+/// Splits written content into chunks and stores them in a `ChunkStore`, the same way
+/// `SelfEncryptor::split` does.
+///
+/// Chunks are stored as soon as they are complete, except for the first two chunks of encrypted
+/// content, which are kept in memory until `finish` because their keys depend on the last chunks.
+pub struct ChunkWriter<S: ChunkStore> {
+    store: S,
+    encryptor: SelfEncryptor,
+    chunks: Vec<ChunkInfo>,
+    // Content of the chunks whose encryption has to wait for `finish`.
+    held: Vec<Vec<u8>>,
+    // Content of the incomplete chunk.
+    buffer: Vec<u8>,
+}
+
+impl<S: ChunkStore> ChunkWriter<S> {
+    /// Creates a writer which splits content as configured by `encryptor`.
+    pub fn new(store: S, encryptor: SelfEncryptor) -> Self {
+        Self {
+            store,
+            encryptor,
+            chunks: Vec::new(),
+            held: Vec::new(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Stores the remaining chunks and returns the data map of the written content.
+    ///
+    /// If the data map would be larger than a chunk, it is stored as chunks too and the returned
+    /// data map describes them instead.
+    pub fn finish(mut self) -> Result<DataMap> {
+        if !self.buffer.is_empty() {
+            let content = mem::replace(&mut self.buffer, Vec::new());
+            self.push_chunk(content)?;
+        }
+        let held = mem::replace(&mut self.held, Vec::new());
+        for (index, content) in held.into_iter().enumerate() {
+            self.store_chunk(index, content)?;
+        }
+
+        let data_map = DataMap::Chunks {
+            chunks: self.chunks,
+            encrypted: self.encryptor.encrypt,
+        };
+        let serialised = utils::serialise(&data_map);
+        if serialised.len() <= self.encryptor.chunk_size {
+            return Ok(data_map);
+        }
+        let mut writer = ChunkWriter::new(self.store, self.encryptor);
+        let mut remaining = &serialised[..];
+        while !remaining.is_empty() {
+            let count = writer.write_part(remaining)?;
+            remaining = &remaining[count..];
+        }
+        Ok(DataMap::Child(Box::new(writer.finish()?)))
+    }
+
+    // Buffers as much of `buf` as fits in the current chunk, and stores the chunk once it is
+    // complete. Nothing is buffered if storing fails, so the write can be retried.
+    fn write_part(&mut self, buf: &[u8]) -> Result<usize> {
+        let len = self.buffer.len();
+        let count = buf.len().min(self.encryptor.chunk_size - len);
+        self.buffer.extend_from_slice(&buf[..count]);
+        if self.buffer.len() == self.encryptor.chunk_size {
+            let content = mem::replace(&mut self.buffer, Vec::new());
+            if let Err(error) = self.push_chunk(content.clone()) {
+                self.buffer = content;
+                self.buffer.truncate(len);
+                return Err(error);
+            }
+        }
+        Ok(count)
+    }
+
+    // Adds the chunk to the data map once it is stored, or holds it back for `finish`.
+    fn push_chunk(&mut self, content: Vec<u8>) -> Result<()> {
+        let index = self.chunks.len();
+        self.chunks.push(ChunkInfo {
+            name: XorName::default(),
+            source_hash: tiny_keccak::sha3_256(&content),
+            size: content.len() as u64,
+        });
+        if self.encryptor.encrypt && index < 2 {
+            self.held.push(content);
+            return Ok(());
+        }
+        let result = self.store_chunk(index, content);
+        if result.is_err() {
+            let _ = self.chunks.pop();
+        }
+        result
+    }
+
+    fn store_chunk(&mut self, index: usize, mut content: Vec<u8>) -> Result<()> {
+        if self.encryptor.encrypt {
+            apply_keystream(&mut content, &chunk_key(&self.chunks, index));
+        }
+        let data = ImmutableData::new(content);
+        let name = *data.name();
+        self.store.put(data)?;
+        self.chunks[index].name = name;
+        Ok(())
+    }
+}
+
+impl<S: ChunkStore> Write for ChunkWriter<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_part(buf).map_err(io_error)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MIN_CHUNK_SIZE;
+    use unwrap::unwrap;
+
+    // Store which counts the chunks fetched from it.
+    #[derive(Default)]
+    struct CountingStore {
+        chunks: BTreeMap<XorName, ImmutableData>,
+        fetched: usize,
+    }
+
+    impl ChunkStore for CountingStore {
+        fn get(&mut self, name: &XorName) -> Result<ImmutableData> {
+            self.fetched += 1;
+            ChunkStore::get(&mut self.chunks, name)
+        }
+
+        fn put(&mut self, data: ImmutableData) -> Result<()> {
+            self.chunks.put(data)
+        }
+    }
+
+    #[test]
+    fn write_then_read() {
+        let content: Vec<u8> = (0..20 * MIN_CHUNK_SIZE + 100)
+            .map(|_| rand::random())
+            .collect();
+        let encryptor = unwrap!(SelfEncryptor::new().with_chunk_size(MIN_CHUNK_SIZE)).encrypted();
+
+        let mut store = CountingStore::default();
+        let mut writer = ChunkWriter::new(&mut store, encryptor);
+        for part in content.chunks(700) {
+            unwrap!(writer.write_all(part));
+        }
+        let data_map = unwrap!(writer.finish());
+        // The writer produces the same chunks as splitting the whole content at once.
+        let (split_map, chunks) = encryptor.split(&content);
+        assert_eq!(data_map, split_map);
+        assert!(chunks
+            .iter()
+            .all(|data| store.chunks.contains_key(data.name())));
+
+        let mut reader = unwrap!(ChunkReader::new(&mut store, &data_map));
+        assert_eq!(reader.len(), content.len() as u64);
+        let mut read = Vec::new();
+        let _ = unwrap!(reader.read_to_end(&mut read));
+        assert_eq!(read, content);
+
+        // Random access only fetches the chunks it needs.
+        let fetched = store.fetched;
+        let mut reader = unwrap!(ChunkReader::new(&mut store, &data_map));
+        let position = 5 * MIN_CHUNK_SIZE as u64 - 10;
+        assert_eq!(unwrap!(reader.seek(SeekFrom::Start(position))), position);
+        let mut buf = [0; 20];
+        unwrap!(reader.read_exact(&mut buf));
+        assert_eq!(buf[..], content[position as usize..position as usize + 20]);
+        assert_eq!(
+            unwrap!(reader.seek(SeekFrom::End(-5))),
+            content.len() as u64 - 5
+        );
+        let mut tail = Vec::new();
+        let _ = unwrap!(reader.read_to_end(&mut tail));
+        assert_eq!(tail[..], content[content.len() - 5..]);
+        assert!(reader
+            .seek(SeekFrom::Current(-(content.len() as i64) - 1))
+            .is_err());
+        // The chunks of the child data map, and the three chunks read.
+        let data_map_chunks = chunks.len() - 21;
+        assert!(data_map_chunks > 0);
+        assert_eq!(store.fetched - fetched, data_map_chunks + 3);
+    }
+
+    // Store which fails to store chunks while `full` is set.
+    #[derive(Default)]
+    struct FlakyStore {
+        chunks: BTreeMap<XorName, ImmutableData>,
+        full: bool,
+    }
+
+    impl ChunkStore for FlakyStore {
+        fn get(&mut self, name: &XorName) -> Result<ImmutableData> {
+            ChunkStore::get(&mut self.chunks, name)
+        }
+
+        fn put(&mut self, data: ImmutableData) -> Result<()> {
+            if self.full {
+                return Err(Error::NetworkOther("full".to_string()));
+            }
+            self.chunks.put(data)
+        }
+    }
+
+    #[test]
+    fn failed_put() {
+        let content: Vec<u8> = (0..5 * MIN_CHUNK_SIZE).map(|_| rand::random()).collect();
+        let encryptor = unwrap!(SelfEncryptor::new().with_chunk_size(MIN_CHUNK_SIZE)).encrypted();
+        let mut store = FlakyStore::default();
+        let mut writer = ChunkWriter::new(&mut store, encryptor);
+        unwrap!(writer.write_all(&content[..3 * MIN_CHUNK_SIZE - 1]));
+
+        // The write completing the chunk fails and leaves no trace of it.
+        writer.store.full = true;
+        assert!(writer
+            .write_all(&content[3 * MIN_CHUNK_SIZE - 1..])
+            .is_err());
+        assert_eq!(writer.chunks.len(), 2);
+        assert_eq!(writer.buffer.len(), MIN_CHUNK_SIZE - 1);
+
+        // The store errors are returned as they are.
+        writer.store.full = false;
+        unwrap!(writer.write_all(&content[3 * MIN_CHUNK_SIZE - 1..4 * MIN_CHUNK_SIZE]));
+        writer.store.full = true;
+        match writer.finish() {
+            Err(Error::NetworkOther(ref message)) if message == "full" => (),
+            x => panic!("Unexpected result: {:?}", x),
+        }
+
+        // Retrying the failed write produces the same content.
+        store.full = false;
+        let mut writer = ChunkWriter::new(&mut store, encryptor);
+        unwrap!(writer.write_all(&content[..3 * MIN_CHUNK_SIZE - 1]));
+        writer.store.full = true;
+        assert!(writer
+            .write_all(&content[3 * MIN_CHUNK_SIZE - 1..])
+            .is_err());
+        writer.store.full = false;
+        unwrap!(writer.write_all(&content[3 * MIN_CHUNK_SIZE - 1..]));
+        let data_map = unwrap!(writer.finish());
+        assert_eq!(data_map, encryptor.split(&content).0);
+        assert_eq!(unwrap!(data_map.join(|name| store.get(name))), content);
+    }
+
+    #[test]
+    fn tampered_chunk() {
+        let content: Vec<u8> = (0..3 * MIN_CHUNK_SIZE).map(|_| rand::random()).collect();
+        let encryptor = unwrap!(SelfEncryptor::new().with_chunk_size(MIN_CHUNK_SIZE));
+        let mut store = BTreeMap::new();
+        let mut writer = ChunkWriter::new(&mut store, encryptor);
+        unwrap!(writer.write_all(&content));
+        let data_map = unwrap!(writer.finish());
+
+        // Replace the second chunk with different content stored under its name.
+        let name = match data_map {
+            DataMap::Chunks { ref chunks, .. } => chunks[1].name,
+            DataMap::Child(_) => panic!("Unexpected child data map"),
+        };
+        let _ = store.insert(name, ImmutableData::new(b"forged".to_vec()));
+
+        let mut reader = unwrap!(ChunkReader::new(&mut store, &data_map));
+        let mut read = Vec::new();
+        match reader.read_to_end(&mut read) {
+            Err(ref error) if error.kind() == io::ErrorKind::InvalidData => (),
+            x => panic!("Unexpected result: {:?}", x),
+        }
+        assert_eq!(read[..], content[..MIN_CHUNK_SIZE]);
+    }
+}
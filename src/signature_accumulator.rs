@@ -0,0 +1,169 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Error, PublicKey, Result, Signature};
+use std::collections::BTreeMap;
+use threshold_crypto::{PublicKeySet, PublicKeyShare, SignatureShare};
+
+/// Collects BLS signature shares over a single payload and combines them into a section
+/// signature once enough valid shares have been received.
+#[derive(Clone, Debug)]
+pub struct SignatureAccumulator {
+    public_key_set: PublicKeySet,
+    payload: Vec<u8>,
+    shares: BTreeMap<usize, SignatureShare>,
+    signature: Option<Signature>,
+}
+
+impl SignatureAccumulator {
+    /// Creates a new accumulator for signatures over `payload` by the section with the given
+    /// public key set.
+    pub fn new(public_key_set: PublicKeySet, payload: Vec<u8>) -> Self {
+        Self {
+            public_key_set,
+            payload,
+            shares: BTreeMap::new(),
+            signature: None,
+        }
+    }
+
+    /// Returns the payload being signed.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Returns the section public key the combined signature is verifiable against.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::Bls(self.public_key_set.public_key())
+    }
+
+    /// Returns the number of valid shares received so far.
+    pub fn num_shares(&self) -> usize {
+        self.shares.len()
+    }
+
+    /// Returns the combined signature, if enough shares have been received.
+    pub fn signature(&self) -> Option<&Signature> {
+        self.signature.as_ref()
+    }
+
+    /// Adds the signature share of the elder with the given index.
+    ///
+    /// Returns `Error::InvalidSignature` if `public_key_share` is not the key share at `index` in
+    /// the section's public key set, or if `signature_share` is not valid for the payload.
+    /// Returns the combined `Signature::Bls` once more than `threshold` shares have been added.
+    pub fn add(
+        &mut self,
+        index: usize,
+        public_key_share: PublicKeyShare,
+        signature_share: SignatureShare,
+    ) -> Result<Option<Signature>> {
+        if let Some(signature) = &self.signature {
+            return Ok(Some(signature.clone()));
+        }
+        if public_key_share != self.public_key_set.public_key_share(index)
+            || !public_key_share.verify(&signature_share, &self.payload)
+        {
+            return Err(Error::InvalidSignature);
+        }
+        let _ = self.shares.insert(index, signature_share);
+        if self.shares.len() <= self.public_key_set.threshold() {
+            return Ok(None);
+        }
+
+        let signature = self
+            .public_key_set
+            .combine_signatures(
+                self.shares
+                    .iter()
+                    .map(|(index, share)| (*index as u64, share)),
+            )
+            .map_err(|_| Error::InvalidSignature)?;
+        if !self
+            .public_key_set
+            .public_key()
+            .verify(&signature, &self.payload)
+        {
+            return Err(Error::InvalidSignature);
+        }
+        let signature = Signature::Bls(signature);
+        self.signature = Some(signature.clone());
+        Ok(Some(signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use threshold_crypto::SecretKeySet;
+    use unwrap::unwrap;
+
+    #[test]
+    fn accumulate() {
+        let mut rng = rand::thread_rng();
+        let secret_key_set = SecretKeySet::random(2, &mut rng);
+        let public_key_set = secret_key_set.public_keys();
+        let payload = b"section message".to_vec();
+        let mut accumulator = SignatureAccumulator::new(public_key_set.clone(), payload.clone());
+
+        let share = |index: usize| {
+            let secret_key_share = secret_key_set.secret_key_share(index);
+            (
+                secret_key_share.public_key_share(),
+                secret_key_share.sign(&payload),
+            )
+        };
+
+        // Share signed by a different elder than claimed.
+        let (public_key_share, signature_share) = share(1);
+        assert_eq!(
+            accumulator.add(0, public_key_share, signature_share),
+            Err(Error::InvalidSignature)
+        );
+
+        // Share over a different payload.
+        let secret_key_share = secret_key_set.secret_key_share(0usize);
+        assert_eq!(
+            accumulator.add(
+                0,
+                secret_key_share.public_key_share(),
+                secret_key_share.sign(b"other message"),
+            ),
+            Err(Error::InvalidSignature)
+        );
+        assert_eq!(accumulator.num_shares(), 0);
+
+        for index in 0..2 {
+            let (public_key_share, signature_share) = share(index);
+            assert_eq!(
+                unwrap!(accumulator.add(index, public_key_share, signature_share)),
+                None
+            );
+        }
+        // A duplicate share doesn't count towards the threshold.
+        let (public_key_share, signature_share) = share(1);
+        assert_eq!(
+            unwrap!(accumulator.add(1, public_key_share, signature_share)),
+            None
+        );
+
+        let (public_key_share, signature_share) = share(3);
+        let signature = unwrap!(unwrap!(accumulator.add(
+            3,
+            public_key_share,
+            signature_share
+        )));
+        unwrap!(accumulator.public_key().verify(&signature, &payload));
+        assert_eq!(accumulator.signature(), Some(&signature));
+        assert_eq!(
+            accumulator.public_key(),
+            PublicKey::Bls(public_key_set.public_key())
+        );
+    }
+}
@@ -20,8 +20,12 @@ pub fn verify_signature(
     request: &Request,
     message_id: &MessageId,
 ) -> Result<()> {
-    let message = serialise(&(request, *message_id));
-    public_key.verify(signature, message)
+    public_key.verify(signature, request_payload(request, message_id))
+}
+
+/// Returns the bytes signed by the requester, and by the co-signers, of a request.
+pub fn request_payload(request: &Request, message_id: &MessageId) -> Vec<u8> {
+    serialise(&(request, *message_id))
 }
 
 /// Wrapper for raw bincode::serialize
@@ -47,3 +51,30 @@ pub(crate) fn decode<I: Decodable, O: DeserializeOwned>(encoded: I) -> Result<O>
     }
     Ok(bincode::deserialize(&decoded).map_err(|e| Error::FailedToParse(e.to_string()))?)
 }
+
+/// Returns a seeded RNG for tests, printing the seed so that a failing test can be replayed by
+/// setting the `RANDOM_SEED` env var.
+#[cfg(test)]
+pub(crate) fn get_rng() -> rand_xorshift::XorShiftRng {
+    use rand::SeedableRng;
+    use std::{env, thread};
+
+    let env_var_name = "RANDOM_SEED";
+    let seed = env::var(env_var_name)
+        .ok()
+        .map(|value| {
+            unwrap!(
+                value.parse::<u64>(),
+                "Env var 'RANDOM_SEED={}' is not a valid u64.",
+                value
+            )
+        })
+        .unwrap_or_else(rand::random);
+    println!(
+        "To replay this '{}', set env var {}={}",
+        unwrap!(thread::current().name()),
+        env_var_name,
+        seed
+    );
+    rand_xorshift::XorShiftRng::seed_from_u64(seed)
+}
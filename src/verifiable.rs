@@ -0,0 +1,25 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::Result;
+
+/// Integrity checks for data fetched from the network.
+///
+/// Data is verified against the address it was requested at, so that a misbehaving or faulty
+/// node can't answer a request with other data, or with data whose structure has been tampered
+/// with.
+pub trait Verifiable {
+    /// Type of the address the data is stored at.
+    type Address;
+
+    /// Checks that the data is stored at `address` and that its structure is consistent.
+    ///
+    /// Returns `Error::InvalidData` describing the first inconsistency found.
+    fn verify(&self, address: &Self::Address) -> Result<()>;
+}